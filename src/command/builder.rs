@@ -4,6 +4,8 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
+use alloc::vec::Vec;
+
 use super::{Command, Header};
 
 /// Command build helper
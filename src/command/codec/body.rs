@@ -0,0 +1,55 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::io::{self, Read};
+
+#[cfg(feature = "futures")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "futures")]
+use futures::{ready, AsyncRead};
+
+use super::CommandCodec;
+
+/// Length limited reader reading body of command from [CommandCodec].
+/// Created by [CommandCodec::body_reader].
+#[derive(Debug)]
+pub struct BodyReader<'a, S> {
+    codec: &'a mut CommandCodec<S>,
+}
+
+impl<'a, S> BodyReader<'a, S> {
+    pub(super) fn new(codec: &'a mut CommandCodec<S>) -> Self {
+        Self { codec }
+    }
+
+    /// Size of body not read yet
+    pub fn remaining(&self) -> usize {
+        self.codec.body_remaining()
+    }
+}
+
+impl<S: Read> Read for BodyReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.codec.read_body_some(buf)?)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncRead + Unpin> AsyncRead for BodyReader<'_, S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let read = ready!(self.codec.poll_read_body_some(cx, buf))?;
+
+        Poll::Ready(Ok(read))
+    }
+}
@@ -4,21 +4,45 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use std::io::Cursor;
-
-use byteorder::{LittleEndian, ReadBytesExt};
+use alloc::vec;
+#[cfg(feature = "bytes")]
+use bytes::{Buf, Bytes};
 
 use crate::command::{Command, HEADER_SIZE, HEAD_SIZE, Header};
+#[cfg(feature = "bytes")]
+use crate::command::{CommandParseError, CommandRef};
+
+/// Decode [Header] and data_size.
+/// Returns tuple with [Header] and data_size.
+///
+/// # Panics
+/// Panics if buf is shorter than [HEAD_SIZE].
+pub fn decode_header(buf: &[u8]) -> (Header, usize) {
+    let header = Header::from_bytes(buf[..HEADER_SIZE].try_into().unwrap());
+    let data_size = u32::from_le_bytes(buf[HEADER_SIZE..HEAD_SIZE].try_into().unwrap());
 
-use super::StreamError;
+    (header, data_size as usize)
+}
 
 /// Decode [Header] and data_size into empty [Command].
-pub fn decode_head(buf: &[u8]) -> Result<Command, StreamError> {
-    let header = bincode::deserialize::<Header>(&buf[..HEADER_SIZE])?;
-    let data_size = Cursor::new(&buf[HEADER_SIZE..HEAD_SIZE]).read_u32::<LittleEndian>()?;
+pub fn decode_head(buf: &[u8]) -> Command {
+    let (header, data_size) = decode_header(buf);
 
-    Ok(Command {
+    Command {
         header,
-        data: vec![0_u8; data_size as usize]
-    })
-}
\ No newline at end of file
+        data: vec![0_u8; data_size]
+    }
+}
+
+/// Decode one [Command] from front of buf, slicing data out of it without copy.
+/// Leaves buf untouched if it returns error, like when buf does not contain whole command.
+#[cfg(feature = "bytes")]
+pub fn decode_command_bytes(buf: &mut Bytes) -> Result<Command<Bytes>, CommandParseError> {
+    let (command, size) = CommandRef::parse(buf)?;
+    let header = command.header.to_header();
+
+    buf.advance(HEAD_SIZE);
+    let data = buf.split_to(size - HEAD_SIZE);
+
+    Ok(Command { header, data })
+}
@@ -0,0 +1,80 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use alloc::vec::Vec;
+
+use crate::command::{Command, CommandParseError, CommandRef};
+
+/// Sans-IO command decoder.
+/// Feed bytes received from any transport and take decoded commands out of it.
+#[derive(Debug, Default)]
+pub struct CommandDecoder {
+    buf: Vec<u8>,
+    start: usize,
+}
+
+impl CommandDecoder {
+    pub const fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+        }
+    }
+
+    /// Append received bytes
+    pub fn feed(&mut self, data: &[u8]) {
+        // Drop consumed bytes before growing buffer
+        if self.start > 0 && self.buf.len() + data.len() > self.buf.capacity() {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Bytes fed but not decoded yet
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    /// Size of bytes needed to decode next command.
+    /// Returns [usize::MAX] if next command is too large to ever be decoded.
+    pub fn needed(&self) -> usize {
+        match CommandRef::parse(self.buffered()) {
+            Ok(_) => 0,
+            Err(CommandParseError::Incomplete { expected, got }) => expected - got,
+            Err(CommandParseError::TooLarge { .. }) => usize::MAX,
+        }
+    }
+
+    /// Decode next command.
+    /// Returns [None] if whole command is not fed yet.
+    pub fn decode(&mut self) -> Option<Command> {
+        let (command, size) = CommandRef::parse(self.buffered()).ok()?;
+        let command = command.to_owned();
+
+        self.start += size;
+        if self.start == self.buf.len() {
+            self.buf.clear();
+            self.start = 0;
+        }
+
+        Some(command)
+    }
+
+    /// Returns true if no partial command is buffered
+    pub fn is_empty(&self) -> bool {
+        self.buffered().is_empty()
+    }
+}
+
+impl Iterator for CommandDecoder {
+    type Item = Command;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decode()
+    }
+}
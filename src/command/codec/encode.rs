@@ -4,15 +4,37 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use byteorder::{LittleEndian, WriteBytesExt};
+use alloc::vec::Vec;
 
-use crate::command::Command;
+use crate::command::{Command, Header, HEADER_SIZE, HEAD_SIZE};
 
 /// Encode header and data_size to bytes.
-/// The result Vec's length is same with HEADER_SIZE + 4.
-pub fn encode_head(command: &Command) -> Result<Vec<u8>, bincode::Error> {
-    let mut head = bincode::serialize(&command.header)?;
-    head.write_u32::<LittleEndian>(command.data.len() as u32)?;
+pub fn encode_head<D: AsRef<[u8]>>(command: &Command<D>) -> [u8; HEAD_SIZE] {
+    encode_raw_head(&command.header, command.data.as_ref().len())
+}
+
+/// Encode header and given data_size to bytes.
+///
+/// # Panics
+/// Panics if data_size is larger than [u32::MAX].
+pub fn encode_raw_head(header: &Header, data_size: usize) -> [u8; HEAD_SIZE] {
+    let mut head = [0_u8; HEAD_SIZE];
+
+    head[..HEADER_SIZE].copy_from_slice(&header.to_bytes());
+    let data_size = u32::try_from(data_size).expect("data_size must fit in u32");
+    head[HEADER_SIZE..].copy_from_slice(&data_size.to_le_bytes());
+
+    head
+}
+
+/// Encode whole command to bytes.
+/// The result Vec's length is same with HEAD_SIZE + data length.
+pub fn encode_frame<D: AsRef<[u8]>>(command: &Command<D>) -> Vec<u8> {
+    let data = command.data.as_ref();
+
+    let mut frame = Vec::with_capacity(HEAD_SIZE + data.len());
+    frame.extend_from_slice(&encode_head(command));
+    frame.extend_from_slice(data);
 
-    Ok(head)
+    frame
 }
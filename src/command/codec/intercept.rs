@@ -0,0 +1,304 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{Read, Write},
+};
+
+#[cfg(feature = "futures")]
+use futures::{AsyncRead, AsyncWrite};
+
+use crate::command::Command;
+
+use super::{CommandCodec, StreamError};
+
+/// Decision of [Interceptor] on a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Pass command, which may be modified, to next interceptor
+    Pass(Command),
+
+    /// Drop command
+    Drop,
+
+    /// Stop command and send given response back to its sender.
+    /// Response passes interceptors between this one and its sender, but cannot be answered again.
+    /// [Action::Respond] on a response drops it.
+    Respond(Command),
+}
+
+/// Destination of command after passing whole [InterceptorChain]
+enum Route {
+    Application(Command),
+    Stream(Command),
+    Dropped,
+}
+
+/// Intercepts commands between [CommandCodec] and application.
+///
+/// Every method passes command unchanged by default.
+pub trait Interceptor: Send {
+    /// Called on command read from stream
+    fn inbound(&mut self, command: Command) -> Action {
+        Action::Pass(command)
+    }
+
+    /// Called on command written by application
+    fn outbound(&mut self, command: Command) -> Action {
+        Action::Pass(command)
+    }
+}
+
+/// Ordered chain of [Interceptor].
+///
+/// First interceptor is closest to application.
+/// Outbound commands run through interceptors in insertion order, and inbound commands in reverse order.
+#[derive(Default)]
+pub struct InterceptorChain {
+    interceptors: Vec<Box<dyn Interceptor>>,
+}
+
+impl InterceptorChain {
+    pub const fn new() -> Self {
+        Self {
+            interceptors: Vec::new(),
+        }
+    }
+
+    /// Append interceptor closer to stream than existing ones
+    pub fn with(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.push(interceptor);
+
+        self
+    }
+
+    /// Append interceptor closer to stream than existing ones
+    pub fn push(&mut self, interceptor: impl Interceptor + 'static) {
+        self.interceptors.push(Box::new(interceptor));
+    }
+
+    pub fn len(&self) -> usize {
+        self.interceptors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interceptors.is_empty()
+    }
+
+    /// Run command read from stream through chain
+    pub fn inbound(&mut self, command: Command) -> Action {
+        self.inbound_before(self.interceptors.len(), command).1
+    }
+
+    /// Run command written by application through chain
+    pub fn outbound(&mut self, command: Command) -> Action {
+        self.outbound_after(0, command).1
+    }
+
+    /// Run inbound command through interceptors before index `end`.
+    /// Returns index of interceptor stopped command with action.
+    fn inbound_before(&mut self, end: usize, mut command: Command) -> (usize, Action) {
+        for (i, interceptor) in self.interceptors[..end].iter_mut().enumerate().rev() {
+            command = match interceptor.inbound(command) {
+                Action::Pass(command) => command,
+                action => return (i, action),
+            };
+        }
+
+        (0, Action::Pass(command))
+    }
+
+    /// Run outbound command through interceptors from index `start`.
+    /// Returns index of interceptor stopped command with action.
+    fn outbound_after(&mut self, start: usize, mut command: Command) -> (usize, Action) {
+        for (i, interceptor) in self.interceptors.iter_mut().enumerate().skip(start) {
+            command = match interceptor.outbound(command) {
+                Action::Pass(command) => command,
+                action => return (i, action),
+            };
+        }
+
+        (self.interceptors.len(), Action::Pass(command))
+    }
+
+    /// Route command read from stream, sending response back through rest of chain
+    fn route_inbound(&mut self, command: Command) -> Route {
+        match self.inbound_before(self.interceptors.len(), command) {
+            (_, Action::Pass(command)) => Route::Application(command),
+
+            (_, Action::Drop) => Route::Dropped,
+
+            (i, Action::Respond(response)) => match self.outbound_after(i + 1, response) {
+                (_, Action::Pass(response)) => Route::Stream(response),
+                _ => Route::Dropped,
+            },
+        }
+    }
+
+    /// Route command written by application, sending response back through rest of chain
+    fn route_outbound(&mut self, command: Command) -> Route {
+        match self.outbound_after(0, command) {
+            (_, Action::Pass(command)) => Route::Stream(command),
+
+            (_, Action::Drop) => Route::Dropped,
+
+            (i, Action::Respond(response)) => match self.inbound_before(i, response) {
+                (_, Action::Pass(response)) => Route::Application(response),
+                _ => Route::Dropped,
+            },
+        }
+    }
+}
+
+impl fmt::Debug for InterceptorChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InterceptorChain")
+            .field("len", &self.interceptors.len())
+            .finish()
+    }
+}
+
+/// [CommandCodec] running every command through [InterceptorChain].
+///
+/// Responses to outbound commands given by [Action::Respond] are returned by next read.
+/// Responses to inbound commands are written to stream during read.
+///
+/// It cannot be split. Responses to outbound commands are queued in this value
+/// and only returned by read on it, so reading and writing from different tasks
+/// needs to share it behind a lock.
+#[derive(Debug)]
+pub struct InterceptedCodec<S> {
+    codec: CommandCodec<S>,
+    chain: InterceptorChain,
+
+    /// Responses to outbound commands not read yet
+    responses: VecDeque<Command>,
+}
+
+impl<S> InterceptedCodec<S> {
+    pub fn new(codec: CommandCodec<S>, chain: InterceptorChain) -> Self {
+        Self {
+            codec,
+            chain,
+            responses: VecDeque::new(),
+        }
+    }
+
+    pub const fn codec(&self) -> &CommandCodec<S> {
+        &self.codec
+    }
+
+    /// Codec without interception.
+    /// Commands read or written using it bypass chain.
+    pub fn codec_mut(&mut self) -> &mut CommandCodec<S> {
+        &mut self.codec
+    }
+
+    pub fn chain_mut(&mut self) -> &mut InterceptorChain {
+        &mut self.chain
+    }
+
+    /// Unwrap codec and chain. Responses not read yet are discarded.
+    pub fn into_inner(self) -> (CommandCodec<S>, InterceptorChain) {
+        (self.codec, self.chain)
+    }
+}
+
+impl<S: Write> InterceptedCodec<S> {
+    /// Run command through chain and write it.
+    /// Returns size written, which is 0 if command was dropped or answered by interceptor.
+    pub fn write(&mut self, command: Command) -> Result<usize, StreamError> {
+        match self.chain.route_outbound(command) {
+            Route::Stream(command) => self.codec.write(&command),
+
+            Route::Application(response) => {
+                self.responses.push_back(response);
+                Ok(0)
+            }
+
+            Route::Dropped => Ok(0),
+        }
+    }
+}
+
+impl<S: Read + Write> InterceptedCodec<S> {
+    /// Read next command passing chain.
+    /// Returns [None] if stream ended cleanly before next command.
+    pub fn read(&mut self) -> Result<Option<Command>, StreamError> {
+        if let Some(response) = self.responses.pop_front() {
+            return Ok(Some(response));
+        }
+
+        loop {
+            let command = match self.codec.read()? {
+                Some((_, command)) => command,
+                None => return Ok(None),
+            };
+
+            match self.chain.route_inbound(command) {
+                Route::Application(command) => return Ok(Some(command)),
+
+                Route::Stream(response) => {
+                    self.codec.write(&response)?;
+                }
+
+                Route::Dropped => {}
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncWrite + Unpin> InterceptedCodec<S> {
+    /// Run command through chain and write it async.
+    /// See [InterceptedCodec::write].
+    pub async fn write_async(&mut self, command: Command) -> Result<usize, StreamError> {
+        match self.chain.route_outbound(command) {
+            Route::Stream(command) => self.codec.write_async(&command).await,
+
+            Route::Application(response) => {
+                self.responses.push_back(response);
+                Ok(0)
+            }
+
+            Route::Dropped => Ok(0),
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncRead + AsyncWrite + Unpin> InterceptedCodec<S> {
+    /// Read next command passing chain async.
+    /// See [InterceptedCodec::read].
+    ///
+    /// # Cancel safety
+    /// This method is not cancel safe if interceptor responds to inbound command.
+    /// Response may be partially written if future is dropped.
+    pub async fn read_async(&mut self) -> Result<Option<Command>, StreamError> {
+        if let Some(response) = self.responses.pop_front() {
+            return Ok(Some(response));
+        }
+
+        loop {
+            let command = match self.codec.read_async().await? {
+                Some((_, command)) => command,
+                None => return Ok(None),
+            };
+
+            match self.chain.route_inbound(command) {
+                Route::Application(command) => return Ok(Some(command)),
+
+                Route::Stream(response) => {
+                    self.codec.write_async(&response).await?;
+                }
+
+                Route::Dropped => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+#[cfg(feature = "futures")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "futures")]
+use futures::{ready, AsyncRead, AsyncWrite};
+
+use crate::metrics::CommandMetrics;
+
+use super::CommandCodec;
+
+/// Raw byte transfer on stream of [CommandCodec].
+///
+/// LOCO media connections exchange command like POST, MPOST or DOWN first,
+/// then send file contents as raw bytes on same connection.
+/// Reads and writes are limited to bytes left in transfer,
+/// so [RawTransfer::into_codec] can switch back to command framing after it completes.
+///
+/// It can wrap [SecureStream](crate::secure::stream::SecureStream) directly too.
+pub struct RawTransfer<S> {
+    stream: S,
+
+    offset: u64,
+    total: u64,
+
+    progress: Option<Box<dyn FnMut(u64, u64) + Send>>,
+
+    /// Metrics of codec restored by [RawTransfer::into_codec]
+    metrics: Option<Arc<CommandMetrics>>,
+
+    /// Body tracing of codec restored by [RawTransfer::into_codec]
+    trace_body: bool,
+}
+
+impl<S> RawTransfer<S> {
+    /// Start transfer of `total` bytes resuming from `offset`.
+    ///
+    /// # Panics
+    /// Panics if `offset` is greater than `total`.
+    pub fn new(stream: S, offset: u64, total: u64) -> Self {
+        assert!(offset <= total, "offset must not be greater than total");
+
+        Self {
+            stream,
+            offset,
+            total,
+            progress: None,
+            metrics: None,
+            trace_body: false,
+        }
+    }
+
+    /// Call `progress` with transferred offset and total size every time bytes are transferred
+    pub fn with_progress(mut self, progress: impl FnMut(u64, u64) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+
+        self
+    }
+
+    /// Offset of next byte, including resumed offset
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub const fn remaining(&self) -> u64 {
+        self.total - self.offset
+    }
+
+    pub const fn is_complete(&self) -> bool {
+        self.offset >= self.total
+    }
+
+    pub const fn stream(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Switch back to command framing
+    pub fn into_codec(self) -> CommandCodec<S> {
+        CommandCodec {
+            metrics: self.metrics,
+            trace_body: self.trace_body,
+            ..CommandCodec::new(self.stream)
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Limit buffer length to bytes left
+    fn limit(&self, len: usize) -> usize {
+        self.remaining().min(len as u64) as usize
+    }
+
+    fn advance(&mut self, amt: usize) {
+        self.offset += amt as u64;
+
+        if let Some(progress) = &mut self.progress {
+            progress(self.offset, self.total);
+        }
+    }
+
+    /// Check read size. Stream ended before transfer completes if nothing was read.
+    fn check_read(&mut self, read: usize) -> io::Result<usize> {
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Stream ended before raw transfer completes",
+            ));
+        }
+
+        self.advance(read);
+
+        Ok(read)
+    }
+}
+
+impl<S> CommandCodec<S> {
+    /// Switch to raw byte transfer of `total` bytes resuming from `offset`.
+    ///
+    /// It must be called between commands.
+    /// Returns [PartialCommandError] containing codec if command is partially read,
+    /// like when body of last read head is not read or skipped yet.
+    ///
+    /// # Panics
+    /// Panics if `offset` is greater than `total`.
+    #[allow(clippy::result_large_err)]
+    pub fn into_raw(self, offset: u64, total: u64) -> Result<RawTransfer<S>, PartialCommandError<S>> {
+        if !self.read_state.is_idle() {
+            return Err(PartialCommandError(self));
+        }
+
+        Ok(RawTransfer {
+            metrics: self.metrics,
+            trace_body: self.trace_body,
+            ..RawTransfer::new(self.stream, offset, total)
+        })
+    }
+}
+
+/// Error returned by [CommandCodec::into_raw] if command is partially read.
+/// Contains codec given.
+pub struct PartialCommandError<S>(pub CommandCodec<S>);
+
+impl<S> fmt::Debug for PartialCommandError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PartialCommandError(..)")
+    }
+}
+
+impl<S> fmt::Display for PartialCommandError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tried to start raw transfer in middle of command")
+    }
+}
+
+impl<S> Error for PartialCommandError<S> {}
+
+impl<S: fmt::Debug> fmt::Debug for RawTransfer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawTransfer")
+            .field("stream", &self.stream)
+            .field("offset", &self.offset)
+            .field("total", &self.total)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl<S: Read> Read for RawTransfer<S> {
+    /// Read transferred bytes. Returns 0 after transfer completes.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.limit(buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let read = self.stream.read(&mut buf[..len])?;
+
+        self.check_read(read)
+    }
+}
+
+impl<S: Write> Write for RawTransfer<S> {
+    /// Write bytes to transfer. Returns 0 after transfer completes.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.limit(buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let written = self.stream.write(&buf[..len])?;
+        self.advance(written);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncRead + Unpin> AsyncRead for RawTransfer<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        let len = this.limit(buf.len());
+        if len == 0 {
+            return Poll::Ready(Ok(0));
+        }
+
+        let read = ready!(Pin::new(&mut this.stream).poll_read(cx, &mut buf[..len]))?;
+
+        Poll::Ready(this.check_read(read))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for RawTransfer<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        let len = this.limit(buf.len());
+        if len == 0 {
+            return Poll::Ready(Ok(0));
+        }
+
+        let written = ready!(Pin::new(&mut this.stream).poll_write(cx, &buf[..len]))?;
+        this.advance(written);
+
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
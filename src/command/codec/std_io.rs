@@ -0,0 +1,856 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+//! [CommandCodec] and other parts of codec needing std
+
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, IoSlice, Read, Write},
+    sync::Arc,
+};
+
+#[cfg(feature = "futures")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "futures")]
+use futures::{
+    future::poll_fn,
+    io::{ReadHalf, WriteHalf},
+    ready, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
+
+use crate::{
+    error::{is_retryable_io, ErrorKind},
+    io_util::{read_full, write_all_vectored},
+};
+
+#[cfg(feature = "futures")]
+use crate::{
+    io_util::{read_full_async, write_all_vectored_async},
+    split::ReuniteError,
+};
+
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+#[cfg(all(feature = "tracing", feature = "futures"))]
+use tracing::Instrument;
+
+use super::{
+    body::BodyReader,
+    decode::decode_header,
+    encode::{encode_head, encode_raw_head},
+};
+use crate::{
+    command::{Command, Header, HEAD_SIZE},
+    metrics::{CommandMetrics, Metrics, Side},
+};
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+
+    /// Stream ended in middle of command.
+    /// `expected` is size of whole command, or head size if head was not complete.
+    TruncatedFrame { expected: usize, got: usize },
+}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "Command stream IO error: {}", err),
+            StreamError::TruncatedFrame { expected, got } => write!(
+                f,
+                "Stream ended in middle of command. expected: {} bytes, got: {} bytes",
+                expected, got
+            ),
+        }
+    }
+}
+
+impl Error for StreamError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StreamError::Io(err) => Some(err),
+            StreamError::TruncatedFrame { .. } => None,
+        }
+    }
+}
+
+impl StreamError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            StreamError::Io(_) => ErrorKind::Io,
+            StreamError::TruncatedFrame { .. } => ErrorKind::Framing,
+        }
+    }
+
+    /// Returns true if reconnecting may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            StreamError::Io(err) => is_retryable_io(err),
+            StreamError::TruncatedFrame { .. } => true,
+        }
+    }
+
+    /// Returns true if reconnecting with same configuration will fail again
+    pub fn is_fatal(&self) -> bool {
+        !self.is_retryable()
+    }
+}
+
+impl From<StreamError> for io::Error {
+    fn from(err: StreamError) -> Self {
+        match err {
+            StreamError::Io(err) => err,
+
+            StreamError::TruncatedFrame { .. } => io::Error::new(io::ErrorKind::UnexpectedEof, err),
+        }
+    }
+}
+
+/// Provide Command read / write operation to stream
+#[derive(Debug)]
+pub struct CommandCodec<S> {
+    pub(super) stream: S,
+
+    pub(super) read_state: ReadState,
+    pub(super) metrics: Option<Arc<CommandMetrics>>,
+
+    /// Record command bodies in trace events
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    pub(super) trace_body: bool,
+}
+
+impl<S> CommandCodec<S> {
+    pub const fn new(stream: S) -> Self {
+        Self {
+            stream,
+            read_state: ReadState::new(),
+            metrics: None,
+            trace_body: false,
+        }
+    }
+
+    /// Report commands and response latency to given [Metrics] as [Side::Client].
+    /// Split halves share it, so responses read by one half are matched with commands written by other.
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        self.with_metrics_as(metrics, Side::Client)
+    }
+
+    /// Report commands and response latency to given [Metrics] as given side of connection
+    pub fn with_metrics_as(mut self, metrics: Arc<dyn Metrics>, side: Side) -> Self {
+        self.metrics = Some(Arc::new(CommandMetrics::new(metrics, side)));
+
+        self
+    }
+
+    /// Record command bodies in `tracing` events.
+    /// Bodies contain credentials and message contents, so only enable it while debugging.
+    #[cfg(feature = "tracing")]
+    pub fn with_body_tracing(mut self) -> Self {
+        self.trace_body = true;
+
+        self
+    }
+
+    pub const fn stream(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Size of unread body of last read head
+    pub fn body_remaining(&self) -> usize {
+        self.read_state.body.as_ref().map_or(0, BodyState::left)
+    }
+
+    /// Reader reading unread body of command.
+    /// It ends after body even if stream has more data.
+    pub fn body_reader(&mut self) -> BodyReader<'_, S> {
+        BodyReader::new(self)
+    }
+
+    fn report_written(&self, header: &Header, size: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.written(header, size);
+        }
+    }
+
+    fn report_read(&self, header: &Header, data_size: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.read(header, HEAD_SIZE + data_size);
+        }
+    }
+}
+
+/// Owned read half of [CommandCodec]
+#[cfg(feature = "futures")]
+pub type CommandReadHalf<S> = CommandCodec<ReadHalf<S>>;
+
+/// Owned write half of [CommandCodec]
+#[cfg(feature = "futures")]
+pub type CommandWriteHalf<S> = CommandCodec<WriteHalf<S>>;
+
+#[cfg(feature = "futures")]
+impl<S: AsyncRead + AsyncWrite> CommandCodec<S> {
+    /// Split into owned read and write half.
+    /// Each half can be used from different task concurrently.
+    ///
+    /// Stream is shared using lock held only while polling, so waiting read never blocks write.
+    /// For [SecureStream](crate::secure::stream::SecureStream),
+    /// wrapping each half from [SecureStream::split](crate::secure::stream::SecureStream::split)
+    /// avoids the lock.
+    pub fn split(self) -> (CommandReadHalf<S>, CommandWriteHalf<S>) {
+        let (read_stream, write_stream) = self.stream.split();
+
+        let read = CommandCodec {
+            stream: read_stream,
+            read_state: self.read_state,
+            metrics: self.metrics.clone(),
+            trace_body: self.trace_body,
+        };
+
+        let write = CommandCodec {
+            stream: write_stream,
+            read_state: ReadState::new(),
+            metrics: self.metrics,
+            trace_body: self.trace_body,
+        };
+
+        (read, write)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: Unpin> CommandReadHalf<S> {
+    /// Reunite halves split by [CommandCodec::split]
+    #[allow(clippy::result_large_err)]
+    pub fn reunite(
+        self,
+        write: CommandWriteHalf<S>,
+    ) -> Result<CommandCodec<S>, ReuniteError<Self, CommandWriteHalf<S>>> {
+        if !self.stream.is_pair_of(&write.stream) {
+            return Err(ReuniteError(self, write));
+        }
+
+        Ok(CommandCodec {
+            stream: self.stream.reunite(write.stream).unwrap(),
+            read_state: self.read_state,
+            metrics: self.metrics,
+            trace_body: self.trace_body,
+        })
+    }
+}
+
+impl<S: Write> CommandCodec<S> {
+    /// Write command to stream as one frame and flush it
+    pub fn write<D: AsRef<[u8]>>(&mut self, command: &Command<D>) -> Result<usize, StreamError> {
+        let data = command.data.as_ref();
+        check_data_size(data.len())?;
+        let head = encode_head(command);
+
+        #[cfg(feature = "tracing")]
+        let _span = trace::command_write_span(&command.header, data.len()).entered();
+        #[cfg(feature = "tracing")]
+        if self.trace_body {
+            trace::command_body(data);
+        }
+
+        write_all_vectored(&mut self.stream, &mut [IoSlice::new(&head), IoSlice::new(data)])?;
+        self.stream.flush()?;
+
+        #[cfg(feature = "tracing")]
+        trace::command_written(data.len() + HEAD_SIZE);
+        self.report_written(&command.header, data.len() + HEAD_SIZE);
+
+        Ok(data.len() + HEAD_SIZE)
+    }
+
+    /// Write command with body of `len` bytes read from reader.
+    /// Body is written in chunks and stream is flushed after each chunk,
+    /// so over [SecureStream](crate::secure::stream::SecureStream) each chunk becomes its own packet.
+    ///
+    /// Returns [io::ErrorKind::InvalidInput] error without writing anything if `len` does not fit in data_size.
+    /// Returns [io::ErrorKind::UnexpectedEof] error if reader ends before `len` bytes.
+    /// Stream is left in middle of command in that case.
+    pub fn write_streaming<R: Read>(
+        &mut self,
+        header: &Header,
+        len: usize,
+        reader: &mut R,
+    ) -> Result<usize, StreamError> {
+        check_data_size(len)?;
+
+        #[cfg(feature = "tracing")]
+        let _span = trace::command_write_span(header, len).entered();
+
+        self.stream.write_all(&encode_raw_head(header, len))?;
+
+        let mut buf = [0_u8; BODY_BUF_SIZE];
+        let mut left = len;
+        while left > 0 {
+            let size = buf.len().min(left);
+            if read_full(reader, &mut buf[..size])? < size {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            self.stream.write_all(&buf[..size])?;
+            self.stream.flush()?;
+            left -= size;
+        }
+        self.stream.flush()?;
+
+        #[cfg(feature = "tracing")]
+        trace::command_written(HEAD_SIZE + len);
+        self.report_written(header, HEAD_SIZE + len);
+
+        Ok(HEAD_SIZE + len)
+    }
+}
+
+impl<S: Read> CommandCodec<S> {
+    /// Read one command from stream.
+    /// Returns tuple with read size and Command,
+    /// or [None] if stream ended cleanly before next command.
+    pub fn read(&mut self) -> Result<Option<(usize, Command)>, StreamError> {
+        #[cfg(feature = "tracing")]
+        let span = trace::command_read_span();
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
+        let (header, data_size) = match self.read_head()? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+
+        #[cfg(feature = "tracing")]
+        trace::command_read_head(&span, &header, data_size);
+
+        let mut command = Command {
+            header,
+            data: vec![0_u8; data_size],
+        };
+        self.read_body(&mut command.data)?;
+
+        #[cfg(feature = "tracing")]
+        if self.trace_body {
+            trace::command_body(&command.data);
+        }
+
+        Ok(Some((HEAD_SIZE + data_size, command)))
+    }
+
+    /// Read head of next command without its body.
+    /// Returns tuple with [Header] and data_size,
+    /// or [None] if stream ended cleanly before next command.
+    ///
+    /// Body must be read using [CommandCodec::read_body], [CommandCodec::copy_body]
+    /// or [CommandCodec::skip_body]. Unread body is skipped on next read.
+    pub fn read_head(&mut self) -> Result<Option<(Header, usize)>, StreamError> {
+        self.skip_body()?;
+        self.read_state.command = None;
+
+        let mut buf = [0u8; HEAD_SIZE];
+        let read = read_full(&mut self.stream, &mut buf)?;
+        if read == 0 {
+            return Ok(None);
+        } else if read < HEAD_SIZE {
+            return Err(StreamError::TruncatedFrame {
+                expected: HEAD_SIZE,
+                got: read,
+            });
+        }
+
+        let (header, data_size) = self.read_state.start_body(&buf);
+        self.report_read(&header, data_size);
+
+        Ok(Some((header, data_size)))
+    }
+
+    /// Read unread body of command into start of buf.
+    /// Returns size of bytes read.
+    ///
+    /// Returns [io::ErrorKind::InvalidInput] error without reading anything if buf is smaller than unread body.
+    /// Use [CommandCodec::body_reader] to read body in parts.
+    pub fn read_body(&mut self, buf: &mut [u8]) -> Result<usize, StreamError> {
+        check_body_buf(buf, self.body_remaining())?;
+
+        let mut read = 0;
+
+        loop {
+            match self.read_body_some(&mut buf[read..]) {
+                Ok(0) => break,
+                Ok(size) => read += size,
+                Err(StreamError::Io(err)) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(read)
+    }
+
+    /// Write unread body of command to writer.
+    /// Returns size of bytes written.
+    pub fn copy_body<W: Write>(&mut self, writer: &mut W) -> Result<u64, StreamError> {
+        let mut buf = [0_u8; BODY_BUF_SIZE];
+        let mut written = 0;
+
+        loop {
+            let read = match self.read_body_some(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(StreamError::Io(err)) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+
+            writer.write_all(&buf[..read])?;
+            written += read as u64;
+        }
+
+        Ok(written)
+    }
+
+    /// Discard unread body of command
+    pub fn skip_body(&mut self) -> Result<(), StreamError> {
+        self.copy_body(&mut io::sink())?;
+
+        Ok(())
+    }
+
+    /// Read part of unread body of command.
+    /// Returns 0 if whole body is read or buf is empty.
+    pub(crate) fn read_body_some(&mut self, buf: &mut [u8]) -> Result<usize, StreamError> {
+        let len = match &self.read_state.body {
+            Some(body) => buf.len().min(body.left()),
+            None => return Ok(0),
+        };
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let read = self.stream.read(&mut buf[..len])?;
+        self.read_state.advance_body(read)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncRead + Unpin> CommandCodec<S> {
+    /// Read one command from stream async.
+    /// Returns tuple with read size and Command,
+    /// or [None] if stream ended cleanly before next command.
+    ///
+    /// # Cancel safety
+    /// This method is cancel safe.
+    /// Partially read command is kept in codec and next call continues reading it,
+    /// so no bytes are lost or read twice if the future is dropped before completion.
+    pub async fn read_async(&mut self) -> Result<Option<(usize, Command)>, StreamError> {
+        let read = poll_fn(|cx| self.poll_read(cx));
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = trace::command_read_span();
+            let res = read.instrument(span.clone()).await;
+
+            if let Ok(Some((_, command))) = &res {
+                trace::command_read_head(&span, &command.header, command.data.len());
+            }
+
+            res
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        read.await
+    }
+
+    /// Poll one command from stream.
+    /// Returns tuple with read size and Command,
+    /// or [None] if stream ended cleanly before next command.
+    pub fn poll_read(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<(usize, Command)>, StreamError>> {
+        if self.read_state.command.is_none() {
+            let (header, data_size) = match ready!(self.poll_read_head(cx))? {
+                Some(head) => head,
+                None => return Poll::Ready(Ok(None)),
+            };
+
+            self.read_state.command = Some(Command {
+                header,
+                data: vec![0_u8; data_size],
+            });
+        }
+
+        let mut command = self.read_state.command.take().unwrap();
+        match self.poll_read_body(cx, &mut command.data) {
+            Poll::Ready(Ok(_)) => {
+                #[cfg(feature = "tracing")]
+                if self.trace_body {
+                    trace::command_body(&command.data);
+                }
+
+                Poll::Ready(Ok(Some((HEAD_SIZE + command.data.len(), command))))
+            }
+
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+
+            Poll::Pending => {
+                self.read_state.command = Some(command);
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Read head of next command without its body async.
+    /// See [CommandCodec::read_head].
+    ///
+    /// # Cancel safety
+    /// This method is cancel safe.
+    pub async fn read_head_async(&mut self) -> Result<Option<(Header, usize)>, StreamError> {
+        poll_fn(|cx| self.poll_read_head(cx)).await
+    }
+
+    /// Poll head of next command without its body.
+    /// Unread body of previous command is skipped first.
+    pub fn poll_read_head(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<(Header, usize)>, StreamError>> {
+        ready!(self.poll_skip_body(cx))?;
+        self.read_state.command = None;
+
+        let state = &mut self.read_state;
+        while state.head_read < HEAD_SIZE {
+            let buf = &mut state.head_buf[state.head_read..];
+            let read = ready!(Pin::new(&mut self.stream).poll_read(cx, buf))?;
+
+            if read == 0 {
+                let got = state.head_read;
+                state.head_read = 0;
+
+                return Poll::Ready(if got == 0 {
+                    Ok(None)
+                } else {
+                    Err(StreamError::TruncatedFrame {
+                        expected: HEAD_SIZE,
+                        got,
+                    })
+                });
+            }
+
+            state.head_read += read;
+        }
+        state.head_read = 0;
+
+        let head_buf = state.head_buf;
+        let (header, data_size) = state.start_body(&head_buf);
+        self.report_read(&header, data_size);
+
+        Poll::Ready(Ok(Some((header, data_size))))
+    }
+
+    /// Read unread body of command into start of buf async.
+    /// See [CommandCodec::read_body].
+    ///
+    /// # Cancel safety
+    /// This method is cancel safe if it is called again with same buf.
+    pub async fn read_body_async(&mut self, buf: &mut [u8]) -> Result<usize, StreamError> {
+        poll_fn(|cx| self.poll_read_body(cx, buf)).await
+    }
+
+    /// Poll reading unread body of command into buf.
+    /// Bytes read in previous polls are kept in buf, so it must be polled again with same buf.
+    ///
+    /// Returns [io::ErrorKind::InvalidInput] error without reading anything if buf is smaller than body.
+    pub fn poll_read_body(
+        &mut self,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, StreamError>> {
+        let mut size = 0;
+
+        while let Some(body) = &self.read_state.body {
+            size = body.size;
+            check_body_buf(buf, size)?;
+
+            let buf = &mut buf[body.read..body.size];
+            ready!(self.poll_read_body_some(cx, buf))?;
+        }
+
+        Poll::Ready(Ok(size))
+    }
+
+    /// Write unread body of command to writer async.
+    /// Returns size of bytes written.
+    ///
+    /// # Cancel safety
+    /// This method is not cancel safe. Body read but not written yet is lost if it is dropped.
+    pub async fn copy_body_async<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<u64, StreamError> {
+        let mut buf = [0_u8; BODY_BUF_SIZE];
+        let mut written = 0;
+
+        loop {
+            let read = poll_fn(|cx| self.poll_read_body_some(cx, &mut buf)).await?;
+            if read == 0 {
+                break;
+            }
+
+            writer.write_all(&buf[..read]).await?;
+            written += read as u64;
+        }
+
+        Ok(written)
+    }
+
+    /// Discard unread body of command async
+    ///
+    /// # Cancel safety
+    /// This method is cancel safe.
+    pub async fn skip_body_async(&mut self) -> Result<(), StreamError> {
+        poll_fn(|cx| self.poll_skip_body(cx)).await
+    }
+
+    /// Poll discarding unread body of command
+    pub fn poll_skip_body(&mut self, cx: &mut Context) -> Poll<Result<(), StreamError>> {
+        let mut buf = [0_u8; BODY_BUF_SIZE];
+
+        while ready!(self.poll_read_body_some(cx, &mut buf))? > 0 {}
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Poll reading part of unread body of command.
+    /// Returns 0 if whole body is read or buf is empty.
+    pub(crate) fn poll_read_body_some(
+        &mut self,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, StreamError>> {
+        let len = match &self.read_state.body {
+            Some(body) => buf.len().min(body.left()),
+            None => return Poll::Ready(Ok(0)),
+        };
+        if len == 0 {
+            return Poll::Ready(Ok(0));
+        }
+
+        let read = ready!(Pin::new(&mut self.stream).poll_read(cx, &mut buf[..len]))?;
+        Poll::Ready(self.read_state.advance_body(read))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: AsyncWrite + Unpin> CommandCodec<S> {
+    /// Write command to stream as one frame and flush it async
+    pub async fn write_async<D: AsRef<[u8]>>(
+        &mut self,
+        command: &Command<D>,
+    ) -> Result<usize, StreamError> {
+        let data = command.data.as_ref();
+        check_data_size(data.len())?;
+        let head = encode_head(command);
+
+        #[cfg(feature = "tracing")]
+        if self.trace_body {
+            trace::command_body(data);
+        }
+
+        let write = async {
+            write_all_vectored_async(
+                &mut self.stream,
+                &mut [IoSlice::new(&head), IoSlice::new(data)],
+            )
+            .await?;
+            self.stream.flush().await?;
+
+            #[cfg(feature = "tracing")]
+            trace::command_written(data.len() + HEAD_SIZE);
+            self.report_written(&command.header, data.len() + HEAD_SIZE);
+
+            Ok(data.len() + HEAD_SIZE)
+        };
+
+        #[cfg(feature = "tracing")]
+        let write = write.instrument(trace::command_write_span(&command.header, data.len()));
+
+        write.await
+    }
+
+    /// Write command with body of `len` bytes read from reader async.
+    /// See [CommandCodec::write_streaming].
+    pub async fn write_streaming_async<R: AsyncRead + Unpin>(
+        &mut self,
+        header: &Header,
+        len: usize,
+        reader: &mut R,
+    ) -> Result<usize, StreamError> {
+        check_data_size(len)?;
+
+        let write = async {
+            self.stream.write_all(&encode_raw_head(header, len)).await?;
+
+            let mut buf = [0_u8; BODY_BUF_SIZE];
+            let mut left = len;
+            while left > 0 {
+                let size = buf.len().min(left);
+                if read_full_async(reader, &mut buf[..size]).await? < size {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+
+                self.stream.write_all(&buf[..size]).await?;
+                self.stream.flush().await?;
+                left -= size;
+            }
+            self.stream.flush().await?;
+
+            #[cfg(feature = "tracing")]
+            trace::command_written(HEAD_SIZE + len);
+            self.report_written(header, HEAD_SIZE + len);
+
+            Ok(HEAD_SIZE + len)
+        };
+
+        #[cfg(feature = "tracing")]
+        let write = write.instrument(trace::command_write_span(header, len));
+
+        write.await
+    }
+}
+
+/// Check if data size fits in u32 data_size field of head
+fn check_data_size(size: usize) -> Result<(), StreamError> {
+    if u32::try_from(size).is_err() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Command body is larger than u32::MAX bytes",
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Check if buf can hold body of given size
+fn check_body_buf(buf: &[u8], size: usize) -> Result<(), StreamError> {
+    if buf.len() < size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Buffer is smaller than command body",
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Size of stack buffer used to copy or skip body
+const BODY_BUF_SIZE: usize = 8 * 1024;
+
+/// Partially read command
+#[derive(Debug)]
+pub(super) struct ReadState {
+    /// Partially read head, used by async reads only
+    #[cfg_attr(not(feature = "futures"), allow(dead_code))]
+    head_buf: [u8; HEAD_SIZE],
+    head_read: usize,
+
+    /// Unread body of last read head
+    body: Option<BodyState>,
+
+    /// Command being read by [CommandCodec::poll_read]
+    command: Option<Command>,
+}
+
+impl ReadState {
+    const fn new() -> Self {
+        Self {
+            head_buf: [0_u8; HEAD_SIZE],
+            head_read: 0,
+            body: None,
+            command: None,
+        }
+    }
+
+    /// Returns true if no command is partially read
+    pub(super) fn is_idle(&self) -> bool {
+        self.head_read == 0 && self.body.is_none()
+    }
+
+    /// Decode head and start reading its body
+    fn start_body(&mut self, head: &[u8]) -> (Header, usize) {
+        let (header, data_size) = decode_header(head);
+
+        #[cfg(feature = "tracing")]
+        trace::command_read(&header, data_size);
+
+        if data_size > 0 {
+            self.body = Some(BodyState {
+                size: data_size,
+                read: 0,
+            });
+        }
+
+        (header, data_size)
+    }
+
+    /// Mark read bytes of body as read.
+    /// Returns [StreamError::TruncatedFrame] if stream ended.
+    fn advance_body(&mut self, read: usize) -> Result<usize, StreamError> {
+        let body = match &mut self.body {
+            Some(body) => body,
+            None => return Ok(0),
+        };
+
+        if read == 0 {
+            let err = StreamError::TruncatedFrame {
+                expected: HEAD_SIZE + body.size,
+                got: HEAD_SIZE + body.read,
+            };
+            self.body = None;
+
+            return Err(err);
+        }
+
+        body.read += read;
+        if body.left() == 0 {
+            self.body = None;
+        }
+
+        Ok(read)
+    }
+}
+
+#[derive(Debug)]
+struct BodyState {
+    size: usize,
+    read: usize,
+}
+
+impl BodyState {
+    const fn left(&self) -> usize {
+        self.size - self.read
+    }
+}
@@ -8,7 +8,15 @@ pub mod builder;
 
 pub mod codec;
 
-use std::string::FromUtf8Error;
+use alloc::{
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
+use core::{
+    error::Error,
+    fmt::{self, Display},
+    str::Utf8Error,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -37,6 +45,31 @@ impl Header {
         self.method = Self::to_method(method);
     }
 
+    /// Encode header to bytes in wire layout described in specification
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut buf = [0_u8; HEADER_SIZE];
+
+        buf[..4].copy_from_slice(&self.id.to_le_bytes());
+        buf[4..6].copy_from_slice(&self.status.to_le_bytes());
+        buf[6..17].copy_from_slice(&self.method);
+        buf[17] = self.data_type as u8;
+
+        buf
+    }
+
+    /// Decode header from bytes in wire layout described in specification
+    pub fn from_bytes(buf: &[u8; HEADER_SIZE]) -> Self {
+        let mut method = [0_u8; 11];
+        method.copy_from_slice(&buf[6..17]);
+
+        Self {
+            id: i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            status: i16::from_le_bytes([buf[4], buf[5]]),
+            method,
+            data_type: buf[17] as i8,
+        }
+    }
+
     pub fn to_method(method: &str) -> [u8; 11] {
         let bytes = method.as_bytes();
         let mut method = [0_u8; 11];
@@ -47,9 +80,146 @@ impl Header {
     }
 }
 
-/// Loco protocol Command packet
+/// Loco protocol Command packet.
+///
+/// With `bytes` feature, `Command<Bytes>` holds data which can be sliced out of
+/// decrypted packet and cloned by incrementing reference count.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct Command {
+pub struct Command<D = Vec<u8>> {
     pub header: Header,
-    pub data: Vec<u8>,
+    pub data: D,
+}
+
+#[cfg(feature = "bytes")]
+impl Command {
+    /// Convert data into [Bytes](bytes::Bytes) without copy
+    pub fn into_bytes(self) -> Command<bytes::Bytes> {
+        Command {
+            header: self.header,
+            data: self.data.into(),
+        }
+    }
+}
+
+/// Borrowed view of [Header] bytes in wire layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderRef<'a> {
+    buf: &'a [u8; HEADER_SIZE],
+}
+
+impl<'a> HeaderRef<'a> {
+    pub const fn new(buf: &'a [u8; HEADER_SIZE]) -> Self {
+        Self { buf }
+    }
+
+    pub fn id(&self) -> i32 {
+        i32::from_le_bytes([self.buf[0], self.buf[1], self.buf[2], self.buf[3]])
+    }
+
+    pub fn status(&self) -> i16 {
+        i16::from_le_bytes([self.buf[4], self.buf[5]])
+    }
+
+    /// Raw method field
+    pub fn method_bytes(&self) -> &'a [u8] {
+        &self.buf[6..17]
+    }
+
+    /// Extract str from method field without copy
+    pub fn method(&self) -> Result<&'a str, Utf8Error> {
+        let method = self.method_bytes();
+        let size = method.iter().position(|&c| c == b'\0').unwrap_or(11);
+
+        core::str::from_utf8(&method[..size])
+    }
+
+    pub fn data_type(&self) -> i8 {
+        self.buf[17] as i8
+    }
+
+    pub const fn as_bytes(&self) -> &'a [u8; HEADER_SIZE] {
+        self.buf
+    }
+
+    pub fn to_header(&self) -> Header {
+        Header::from_bytes(self.buf)
+    }
+}
+
+/// Borrowed view of [Command] parsed out of buffer without copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandRef<'a> {
+    pub header: HeaderRef<'a>,
+    pub data: &'a [u8],
+}
+
+impl<'a> CommandRef<'a> {
+    /// Parse one command from front of buf.
+    /// Returns tuple with [CommandRef] and size of bytes consumed.
+    pub fn parse(buf: &'a [u8]) -> Result<(Self, usize), CommandParseError> {
+        if buf.len() < HEAD_SIZE {
+            return Err(CommandParseError::Incomplete {
+                expected: HEAD_SIZE,
+                got: buf.len(),
+            });
+        }
+
+        let (header, rest) = buf.split_at(HEADER_SIZE);
+        let data_size = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+
+        let size = HEAD_SIZE
+            .checked_add(data_size as usize)
+            .ok_or(CommandParseError::TooLarge { data_size })?;
+        if buf.len() < size {
+            return Err(CommandParseError::Incomplete {
+                expected: size,
+                got: buf.len(),
+            });
+        }
+
+        Ok((
+            Self {
+                header: HeaderRef::new(header.try_into().unwrap()),
+                data: &buf[HEAD_SIZE..size],
+            },
+            size,
+        ))
+    }
+
+    /// Copy into owned [Command]
+    pub fn to_owned(&self) -> Command {
+        Command {
+            header: self.header.to_header(),
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandParseError {
+    /// Buffer does not contain whole command.
+    /// `expected` is size of whole command, or head size if head is not complete.
+    Incomplete { expected: usize, got: usize },
+
+    /// Size of command does not fit in usize
+    TooLarge { data_size: u32 },
+}
+
+impl Display for CommandParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandParseError::Incomplete { expected, got } => write!(
+                f,
+                "Incomplete command. expected: {} bytes, got: {} bytes",
+                expected, got
+            ),
+            CommandParseError::TooLarge { data_size } => write!(
+                f,
+                "Command is too large. data_size: {} bytes",
+                data_size
+            ),
+        }
+    }
 }
+
+impl Error for CommandParseError {}
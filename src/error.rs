@@ -0,0 +1,180 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io,
+};
+
+use crate::command::codec::StreamError;
+
+#[cfg(feature = "secure")]
+use crate::secure::{codec::SecureError, crypto::CryptoError, session::SecureHandshakeError};
+
+/// Category of protocol failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Underlying stream failed
+    Io,
+
+    /// Stream ended in middle of command, packet or handshake
+    Framing,
+
+    /// Data could not be decrypted
+    Crypto,
+
+    /// Handshake was rejected or did not complete
+    Handshake,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Io => write!(f, "IO error"),
+            ErrorKind::Framing => write!(f, "Framing error"),
+            ErrorKind::Crypto => write!(f, "Crypto error"),
+            ErrorKind::Handshake => write!(f, "Handshake error"),
+        }
+    }
+}
+
+/// Error of any layer, classified by [ErrorKind].
+///
+/// Displays as its kind. Original error is its [Error::source].
+/// Errors of this crate carried inside [io::Error] by [crate::secure::stream::SecureStream]
+/// are unwrapped, so they are classified by their actual cause.
+#[derive(Debug)]
+pub struct LocoError {
+    kind: ErrorKind,
+    retryable: bool,
+    inner: Box<dyn Error + Send + Sync>,
+}
+
+impl LocoError {
+    fn new(kind: ErrorKind, retryable: bool, inner: impl Error + Send + Sync + 'static) -> Self {
+        Self {
+            kind,
+            retryable,
+            inner: Box::new(inner),
+        }
+    }
+
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns true if reconnecting may succeed
+    pub const fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
+    /// Returns true if reconnecting with same configuration will fail again
+    pub const fn is_fatal(&self) -> bool {
+        !self.retryable
+    }
+
+    /// Original error
+    pub fn get_ref(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.inner
+    }
+
+    /// Unwrap original error
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync> {
+        self.inner
+    }
+}
+
+impl Display for LocoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl Error for LocoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.inner)
+    }
+}
+
+/// Returns true if io error is caused by lost or interrupted connection
+pub(crate) fn is_retryable_io(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+    )
+}
+
+/// Take error of type `E` out of io error, or give io error back
+fn downcast_io<E: Error + 'static>(err: io::Error) -> Result<E, io::Error> {
+    if !err.get_ref().is_some_and(|inner| inner.is::<E>()) {
+        return Err(err);
+    }
+
+    Ok(*err.into_inner().unwrap().downcast::<E>().unwrap())
+}
+
+impl From<io::Error> for LocoError {
+    fn from(err: io::Error) -> Self {
+        let err = match downcast_io::<StreamError>(err) {
+            Ok(err) => return Self::from(err),
+            Err(err) => err,
+        };
+
+        #[cfg(feature = "secure")]
+        let err = match downcast_io::<SecureError>(err) {
+            Ok(err) => return Self::from(err),
+            Err(err) => match downcast_io::<SecureHandshakeError>(err) {
+                Ok(err) => return Self::from(err),
+                Err(err) => err,
+            },
+        };
+
+        Self::new(ErrorKind::Io, is_retryable_io(&err), err)
+    }
+}
+
+impl From<StreamError> for LocoError {
+    fn from(err: StreamError) -> Self {
+        match err {
+            StreamError::Io(err) => Self::from(err),
+            _ => Self::new(err.kind(), err.is_retryable(), err),
+        }
+    }
+}
+
+#[cfg(feature = "secure")]
+impl From<CryptoError> for LocoError {
+    fn from(err: CryptoError) -> Self {
+        Self::new(ErrorKind::Crypto, false, err)
+    }
+}
+
+#[cfg(feature = "secure")]
+impl From<SecureError> for LocoError {
+    fn from(err: SecureError) -> Self {
+        match err {
+            SecureError::Io(err) => Self::from(err),
+            _ => Self::new(err.kind(), err.is_retryable(), err),
+        }
+    }
+}
+
+#[cfg(feature = "secure")]
+impl From<SecureHandshakeError> for LocoError {
+    fn from(err: SecureHandshakeError) -> Self {
+        Self::new(err.kind(), err.is_retryable(), err)
+    }
+}
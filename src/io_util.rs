@@ -0,0 +1,84 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::io::{self, IoSlice, Read, Write};
+
+#[cfg(feature = "futures")]
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Read until buf is full or stream ends.
+/// Returns size of bytes read, which is less than buf length only if stream ended.
+pub fn read_full<S: Read>(stream: &mut S, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+
+    while read < buf.len() {
+        match stream.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(size) => read += size,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(read)
+}
+
+/// Read until buf is full or stream ends async.
+/// Returns size of bytes read, which is less than buf length only if stream ended.
+#[cfg(feature = "futures")]
+pub async fn read_full_async<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    buf: &mut [u8],
+) -> io::Result<usize> {
+    let mut read = 0;
+
+    while read < buf.len() {
+        match stream.read(&mut buf[read..]).await {
+            Ok(0) => break,
+            Ok(size) => read += size,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(read)
+}
+
+/// Write every buffer to stream using vectored write
+pub fn write_all_vectored<S: Write>(stream: &mut S, mut bufs: &mut [IoSlice]) -> io::Result<()> {
+    IoSlice::advance_slices(&mut bufs, 0);
+
+    while !bufs.is_empty() {
+        match stream.write_vectored(bufs) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => IoSlice::advance_slices(&mut bufs, written),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Write every buffer to stream using vectored write async
+#[cfg(feature = "futures")]
+pub async fn write_all_vectored_async<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    mut bufs: &mut [IoSlice<'_>],
+) -> io::Result<()> {
+    IoSlice::advance_slices(&mut bufs, 0);
+
+    while !bufs.is_empty() {
+        match stream.write_vectored(bufs).await {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => IoSlice::advance_slices(&mut bufs, written),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
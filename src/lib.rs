@@ -4,9 +4,28 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![doc = include_str!("../specification.md")]
 
+extern crate alloc;
+
 pub mod command;
 
+#[cfg(feature = "std")]
+pub mod error;
+
+#[cfg(feature = "std")]
+pub mod metrics;
+
+#[cfg(feature = "secure")]
 pub mod secure;
-mod vec_buf;
+
+#[cfg(feature = "std")]
+pub mod split;
+
+#[cfg(feature = "std")]
+mod io_util;
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "secure")]
+mod read_buf;
@@ -0,0 +1,341 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::command::Header;
+
+/// Observer of per-connection traffic.
+///
+/// Every method does nothing by default, so implementations only override what they export.
+/// Methods are called on hot path and should not block.
+pub trait Metrics: Send + Sync {
+    /// Command was read by [CommandCodec](crate::command::codec::CommandCodec).
+    /// `size` includes head.
+    fn command_read(&self, _method: &str, _size: usize) {}
+
+    /// Command was written by [CommandCodec](crate::command::codec::CommandCodec).
+    /// `size` includes head.
+    fn command_written(&self, _method: &str, _size: usize) {}
+
+    /// Response with same id as earlier request was observed.
+    /// `method` is method of request.
+    ///
+    /// Requests are commands written on [Side::Client] and commands read on [Side::Server].
+    fn response_latency(&self, _method: &str, _latency: Duration) {}
+
+    /// Secure packet was read. `size` includes packet head.
+    fn packet_read(&self, _size: usize) {}
+
+    /// Secure packet was written. `size` includes packet head.
+    fn packet_written(&self, _size: usize) {}
+
+    /// Secure packet could not be decrypted
+    fn decrypt_failed(&self) {}
+
+    /// Server handshake completed.
+    /// `duration` is time from receiving handshake to session key ready.
+    fn handshake(&self, _duration: Duration) {}
+}
+
+/// Side of connection command codec is on.
+/// Decides which direction carries requests when measuring response latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Side {
+    /// Written commands are requests and read commands are responses
+    #[default]
+    Client,
+
+    /// Read commands are requests and written commands are responses
+    Server,
+}
+
+/// [Metrics] ignoring everything
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// [Metrics] with [fmt::Debug] so codecs can derive it
+#[cfg(feature = "secure")]
+#[derive(Clone)]
+pub(crate) struct SharedMetrics(pub Arc<dyn Metrics>);
+
+#[cfg(feature = "secure")]
+impl fmt::Debug for SharedMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+/// Method field as str, or empty str if it is not valid utf-8
+fn method_str(method: &[u8; 11]) -> &str {
+    let size = method.iter().position(|&c| c == b'\0').unwrap_or(11);
+
+    std::str::from_utf8(&method[..size]).unwrap_or_default()
+}
+
+/// Maximum number of requests waiting for response.
+/// Oldest command is evicted and never measured when it is full.
+const MAX_PENDING: usize = 1024;
+
+#[derive(Debug)]
+struct PendingCommand {
+    method: [u8; 11],
+    started_at: Instant,
+    seq: u64,
+}
+
+/// Requests waiting for response
+#[derive(Debug, Default)]
+struct PendingCommands {
+    by_id: HashMap<i32, PendingCommand>,
+
+    /// Order of requests. Entries replaced or answered are skipped lazily.
+    order: VecDeque<(u64, i32)>,
+
+    next_seq: u64,
+}
+
+impl PendingCommands {
+    fn insert(&mut self, id: i32, method: [u8; 11]) {
+        if !self.by_id.contains_key(&id) {
+            while self.by_id.len() >= MAX_PENDING {
+                self.evict_oldest();
+            }
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.by_id.insert(
+            id,
+            PendingCommand {
+                method,
+                started_at: Instant::now(),
+                seq,
+            },
+        );
+        self.order.push_back((seq, id));
+
+        // Drop stale entries so order does not grow with answered commands
+        if self.order.len() > MAX_PENDING * 2 {
+            let by_id = &self.by_id;
+            self.order
+                .retain(|(seq, id)| by_id.get(id).is_some_and(|command| command.seq == *seq));
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((seq, id)) = self.order.pop_front() {
+            if self.by_id.get(&id).is_some_and(|command| command.seq == seq) {
+                self.by_id.remove(&id);
+                return;
+            }
+        }
+    }
+
+    fn remove(&mut self, id: i32) -> Option<PendingCommand> {
+        self.by_id.remove(&id)
+    }
+}
+
+/// [Metrics] of command codec, shared between split halves to match responses
+pub(crate) struct CommandMetrics {
+    metrics: Arc<dyn Metrics>,
+    side: Side,
+
+    /// Method and start time of requests waiting for response
+    pending: Mutex<PendingCommands>,
+}
+
+impl CommandMetrics {
+    pub fn new(metrics: Arc<dyn Metrics>, side: Side) -> Self {
+        Self {
+            metrics,
+            side,
+            pending: Mutex::new(PendingCommands::default()),
+        }
+    }
+
+    pub fn written(&self, header: &Header, size: usize) {
+        self.metrics.command_written(method_str(&header.method), size);
+
+        match self.side {
+            Side::Client => self.request(header),
+            Side::Server => self.response(header),
+        }
+    }
+
+    pub fn read(&self, header: &Header, size: usize) {
+        self.metrics.command_read(method_str(&header.method), size);
+
+        match self.side {
+            Side::Client => self.response(header),
+            Side::Server => self.request(header),
+        }
+    }
+
+    fn request(&self, header: &Header) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(header.id, header.method);
+    }
+
+    fn response(&self, header: &Header) {
+        let request = self
+            .pending
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(header.id);
+
+        if let Some(request) = request {
+            self.metrics
+                .response_latency(method_str(&request.method), request.started_at.elapsed());
+        }
+    }
+}
+
+impl fmt::Debug for CommandMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandMetrics").finish_non_exhaustive()
+    }
+}
+
+/// [Metrics] exporting to recorder installed in `metrics` crate.
+///
+/// Counters are `loco_commands_total`, `loco_command_bytes_total`, `loco_packets_total`,
+/// `loco_packet_bytes_total` labeled with `direction` (`in` or `out`) and `loco_decrypt_failures_total`.
+/// Command counters are labeled with `method` too.
+/// Histograms are `loco_response_latency_seconds` labeled with `method` and `loco_handshake_duration_seconds`.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone)]
+pub struct MetricsAdapter {
+    labels: Vec<::metrics::Label>,
+
+    /// Labels with `direction` added, built once in [MetricsAdapter::with_label]
+    inbound: Vec<::metrics::Label>,
+    outbound: Vec<::metrics::Label>,
+
+    /// Interned method label values, shared between clones
+    methods: Arc<Mutex<HashMap<String, ::metrics::SharedString>>>,
+}
+
+/// Maximum number of interned method label values.
+/// Values of other methods are allocated on every use.
+#[cfg(feature = "metrics")]
+const MAX_METHODS: usize = 256;
+
+#[cfg(feature = "metrics")]
+impl MetricsAdapter {
+    pub fn new() -> Self {
+        Self {
+            labels: Vec::new(),
+            inbound: vec![::metrics::Label::from_static_parts("direction", "in")],
+            outbound: vec![::metrics::Label::from_static_parts("direction", "out")],
+            methods: Arc::default(),
+        }
+    }
+
+    /// Add label to every metric, like connection id
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let label = ::metrics::Label::new(shared_str(key.into()), shared_str(value.into()));
+
+        self.labels.push(label.clone());
+        self.inbound.insert(self.inbound.len() - 1, label.clone());
+        self.outbound.insert(self.outbound.len() - 1, label);
+
+        self
+    }
+
+    /// Labels with `method` added
+    fn with_method(&self, labels: &[::metrics::Label], method: &str) -> Vec<::metrics::Label> {
+        let mut methods = self.methods.lock().unwrap_or_else(|err| err.into_inner());
+        let method = match methods.get(method) {
+            Some(method) => method.clone(),
+
+            None if methods.len() < MAX_METHODS => {
+                let shared = shared_str(method.to_owned());
+                methods.insert(method.to_owned(), shared.clone());
+
+                shared
+            }
+
+            None => method.to_owned().into(),
+        };
+        drop(methods);
+
+        let mut labels = labels.to_vec();
+        labels.push(::metrics::Label::new("method", method));
+
+        labels
+    }
+
+    fn command(&self, labels: &[::metrics::Label], method: &str, size: usize) {
+        ::metrics::counter!("loco_commands_total", self.with_method(labels, method)).increment(1);
+        ::metrics::counter!("loco_command_bytes_total", labels.iter()).increment(size as u64);
+    }
+
+    fn packet(&self, labels: &[::metrics::Label], size: usize) {
+        ::metrics::counter!("loco_packets_total", labels.iter()).increment(1);
+        ::metrics::counter!("loco_packet_bytes_total", labels.iter()).increment(size as u64);
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Default for MetricsAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// [::metrics::SharedString] cloned without allocation
+#[cfg(feature = "metrics")]
+fn shared_str(value: String) -> ::metrics::SharedString {
+    ::metrics::SharedString::from_shared(Arc::from(value))
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics for MetricsAdapter {
+    fn command_read(&self, method: &str, size: usize) {
+        self.command(&self.inbound, method, size);
+    }
+
+    fn command_written(&self, method: &str, size: usize) {
+        self.command(&self.outbound, method, size);
+    }
+
+    fn response_latency(&self, method: &str, latency: Duration) {
+        ::metrics::histogram!(
+            "loco_response_latency_seconds",
+            self.with_method(&self.labels, method)
+        )
+        .record(latency);
+    }
+
+    fn packet_read(&self, size: usize) {
+        self.packet(&self.inbound, size);
+    }
+
+    fn packet_written(&self, size: usize) {
+        self.packet(&self.outbound, size);
+    }
+
+    fn decrypt_failed(&self) {
+        ::metrics::counter!("loco_decrypt_failures_total", self.labels.iter()).increment(1);
+    }
+
+    fn handshake(&self, duration: Duration) {
+        ::metrics::histogram!("loco_handshake_duration_seconds", self.labels.iter())
+            .record(duration);
+    }
+}
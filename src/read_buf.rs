@@ -0,0 +1,58 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::io::{self, Read};
+
+/// Read buffer holding one chunk with read offset.
+/// Leftover data is never copied again.
+#[derive(Debug, Default)]
+pub struct ReadBuf {
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl ReadBuf {
+    pub const fn new() -> Self {
+        Self {
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Clear buffer and returns chunk to fill, keeping its allocation
+    pub fn reuse(&mut self) -> &mut Vec<u8> {
+        self.chunk.clear();
+        self.pos = 0;
+
+        &mut self.chunk
+    }
+
+    /// Unread part of chunk
+    pub fn as_slice(&self) -> &[u8] {
+        &self.chunk[self.pos..]
+    }
+
+    /// Mark amt bytes as read
+    pub fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.chunk.len());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.chunk.len()
+    }
+}
+
+impl Read for ReadBuf {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = self.as_slice();
+        let len = data.len().min(buf.len());
+
+        buf[..len].copy_from_slice(&data[..len]);
+        self.consume(len);
+
+        Ok(len)
+    }
+}
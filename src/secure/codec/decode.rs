@@ -4,21 +4,31 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use std::io::Cursor;
+use crate::secure::{
+    crypto::CryptoError, SecureHeader, SecurePacket, SECURE_HEADER_SIZE, SECURE_HEAD_SIZE,
+};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use super::SecureError;
 
-use crate::secure::{SecureHeader, SecurePacket};
+/// Decode data_size and [SecureHeader].
+/// Returns tuple with encrypted data size and [SecureHeader].
+pub fn decode_secure_header(buf: &[u8]) -> Result<(usize, SecureHeader), SecureError> {
+    let data_size = u32::from_le_bytes(buf[..4].try_into().unwrap());
 
-use super::SecureError;
+    let header = SecureHeader::from_bytes(buf[4..SECURE_HEAD_SIZE].try_into().unwrap());
+    let encrypted_size = (data_size as usize)
+        .checked_sub(SECURE_HEADER_SIZE)
+        .ok_or(CryptoError::CorruptedData)?;
+
+    Ok((encrypted_size, header))
+}
 
 /// Decode data_size and [SecureHeader] into empty [SecurePacket]
 pub fn decode_secure_head(buf: &[u8]) -> Result<SecurePacket, SecureError> {
-    let data_size = Cursor::new(&buf[..4]).read_u32::<LittleEndian>()?;
+    let (encrypted_size, header) = decode_secure_header(buf)?;
 
-    let header = bincode::deserialize::<SecureHeader>(&buf[4..])?;
     Ok(SecurePacket {
         header,
-        data: vec![0_u8; (data_size - 16) as usize]
+        data: vec![0_u8; encrypted_size]
     })
 }
@@ -4,22 +4,36 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use crate::secure::{SecureHeader, crypto::CryptoStore};
+use crate::secure::{SecureHeader, crypto::CryptoStore, SECURE_HEADER_SIZE, SECURE_HEAD_SIZE};
 
 use super::SecureError;
 
 /// Encrypt data using provided [CryptoStore] and make it packet 
 pub fn to_encrypted_packet(crypto: &CryptoStore, data: &[u8]) -> Result<Vec<u8>, SecureError> {
+    let mut packet = Vec::with_capacity(SECURE_HEAD_SIZE + data.len());
+    encode_encrypted_packet(crypto, data, &mut packet)?;
+
+    Ok(packet)
+}
+
+/// Encrypt data using provided [CryptoStore] into packet buffer.
+/// Buffer is cleared first and does not allocate if it has enough capacity.
+pub fn encode_encrypted_packet(
+    crypto: &CryptoStore,
+    data: &[u8],
+    packet: &mut Vec<u8>,
+) -> Result<(), SecureError> {
     let mut iv = [0_u8; 16];
     crypto.gen_random(&mut iv);
 
-    let data_buf = crypto.encrypt_aes(&data, &iv)?;
+    let data_size = (data.len() + SECURE_HEADER_SIZE) as u32;
 
-    let data_size = (data_buf.len() + 16) as u32;
+    packet.clear();
+    packet.extend_from_slice(&data_size.to_le_bytes());
+    packet.extend_from_slice(&SecureHeader { iv }.to_bytes());
+    packet.extend_from_slice(data);
 
-    let header_buf = bincode::serialize(&SecureHeader {
-        iv,
-    })?;
+    crypto.encrypt_aes_in_place(&mut packet[SECURE_HEAD_SIZE..], &iv)?;
 
-    Ok([data_size.to_le_bytes().into(), header_buf, data_buf].concat())
+    Ok(())
 }
@@ -7,25 +7,44 @@
 pub mod decode;
 pub mod encode;
 
-use std::io::{self, Write, Read};
+mod read_ahead;
 
-use futures::{AsyncRead, AsyncWrite, AsyncReadExt, AsyncWriteExt};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{self, Read, Write},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
-use self::{encode::to_encrypted_packet, decode::decode_secure_head};
+use futures::{
+    future::poll_fn,
+    io::{ReadHalf, WriteHalf},
+    ready, AsyncRead, AsyncReadExt, AsyncWrite,
+};
 
-use super::{crypto::{CryptoStore, CryptoError}, SECURE_HEAD_SIZE, SecurePacket};
+use crate::{
+    error::{is_retryable_io, ErrorKind},
+    metrics::{Metrics, SharedMetrics},
+    split::ReuniteError,
+};
+
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+use self::{decode::decode_secure_header, encode::encode_encrypted_packet, read_ahead::ReadAhead};
+
+use super::{crypto::{CryptoStore, CryptoError}, SECURE_HEAD_SIZE, SecureHeader, SecurePacket};
 
 #[derive(Debug)]
 pub enum SecureError {
-    Bincode(bincode::Error),
     Io(io::Error),
     Crypto(CryptoError),
-}
 
-impl From<bincode::Error> for SecureError {
-    fn from(err: bincode::Error) -> Self {
-        Self::Bincode(err)
-    }
+    /// Stream ended in middle of packet.
+    /// `expected` is size of whole packet, or head size if head was not complete.
+    TruncatedFrame { expected: usize, got: usize },
 }
 
 impl From<io::Error> for SecureError {
@@ -40,15 +59,107 @@ impl From<CryptoError> for SecureError {
     }
 }
 
+impl Display for SecureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecureError::Io(err) => write!(f, "Secure stream IO error: {}", err),
+            SecureError::Crypto(_) => write!(f, "Invalid encryption data"),
+            SecureError::TruncatedFrame { expected, got } => write!(
+                f,
+                "Stream ended in middle of packet. expected: {} bytes, got: {} bytes",
+                expected, got
+            ),
+        }
+    }
+}
+
+impl Error for SecureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SecureError::Io(err) => Some(err),
+            SecureError::Crypto(err) => Some(err),
+            SecureError::TruncatedFrame { .. } => None,
+        }
+    }
+}
+
+impl SecureError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SecureError::Io(_) => ErrorKind::Io,
+            SecureError::Crypto(_) => ErrorKind::Crypto,
+            SecureError::TruncatedFrame { .. } => ErrorKind::Framing,
+        }
+    }
+
+    /// Returns true if reconnecting may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SecureError::Io(err) => is_retryable_io(err),
+            SecureError::Crypto(_) => false,
+            SecureError::TruncatedFrame { .. } => true,
+        }
+    }
+
+    /// Returns true if reconnecting with same configuration will fail again
+    pub fn is_fatal(&self) -> bool {
+        !self.is_retryable()
+    }
+}
+
+/// Default size of [SecureCodec] read-ahead buffer
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Provide secure packet read / write operation to stream.
+///
+/// Reads are buffered so one large read can decode several packets.
+/// Bytes read ahead are kept in codec and lost if it is unwrapped.
+///
+/// Packets are encrypted into reused buffer, and [SecureCodec::read_packet_into]
+/// decrypts into caller buffer, so steady state read and write do not allocate.
 #[derive(Debug)]
 pub struct SecureCodec<S> {
     crypto: CryptoStore,
     stream: S,
+
+    read_buf: ReadAhead,
+    write_state: WriteState,
+
+    metrics: Option<SharedMetrics>,
 }
 
 impl<S> SecureCodec<S> {
     pub const fn new(crypto: CryptoStore, stream: S) -> Self {
-        Self { crypto, stream }
+        Self::with_read_buffer_size(crypto, stream, DEFAULT_READ_BUFFER_SIZE)
+    }
+
+    /// Create codec reading ahead up to `size` bytes at once.
+    /// Packets larger than `size` are still read as a whole.
+    pub const fn with_read_buffer_size(crypto: CryptoStore, stream: S, size: usize) -> Self {
+        Self {
+            crypto,
+            stream,
+            read_buf: ReadAhead::new(size),
+            write_state: WriteState::new(),
+            metrics: None,
+        }
+    }
+
+    /// Read ahead up to `size` bytes at once from next read
+    pub(crate) fn set_read_buffer_size(&mut self, size: usize) {
+        self.read_buf.set_capacity(size);
+    }
+
+    /// Report packets and decryption failures to given [Metrics]
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(SharedMetrics(metrics));
+
+        self
+    }
+
+    /// Bytes read ahead from stream but not decoded yet
+    pub fn read_buffer(&self) -> &[u8] {
+        self.read_buf.data()
     }
 
     pub fn crypto(&self) -> &CryptoStore {
@@ -63,67 +174,319 @@ impl<S> SecureCodec<S> {
         &mut self.stream
     }
 
+    /// Unwrap inner stream. Bytes read ahead are discarded.
     pub fn into_inner(self) -> (CryptoStore, S) {
         (self.crypto, self.stream)
     }
+
+    /// Decode and decrypt next packet into data if read-ahead buffer contains whole packet.
+    /// Returns size of bytes needed to decode next packet otherwise.
+    fn decode_packet(
+        &mut self,
+        data: &mut Vec<u8>,
+    ) -> Result<Result<SecureHeader, usize>, SecureError> {
+        let buf = self.read_buf.data();
+        if buf.len() < SECURE_HEAD_SIZE {
+            return Ok(Err(SECURE_HEAD_SIZE));
+        }
+
+        let (encrypted_size, header) = match decode_secure_header(&buf[..SECURE_HEAD_SIZE]) {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                trace::packet_decrypt_failed(&err);
+                self.report(|metrics| metrics.decrypt_failed());
+
+                return Err(err);
+            }
+        };
+        let size = SECURE_HEAD_SIZE.checked_add(encrypted_size).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Secure packet is too large")
+        })?;
+        if buf.len() < size {
+            return Ok(Err(size));
+        }
+
+        data.clear();
+        data.extend_from_slice(&buf[SECURE_HEAD_SIZE..size]);
+        self.read_buf.consume(size);
+
+        if let Err(err) = self.crypto.decrypt_aes_in_place(data, &header.iv) {
+            #[cfg(feature = "tracing")]
+            trace::packet_decrypt_failed(&err);
+            self.report(|metrics| metrics.decrypt_failed());
+
+            return Err(err.into());
+        }
+
+        #[cfg(feature = "tracing")]
+        trace::packet_read(size, data.len());
+        self.report(|metrics| metrics.packet_read(size));
+
+        Ok(Ok(header))
+    }
+
+    fn report(&self, f: impl FnOnce(&dyn Metrics)) {
+        if let Some(SharedMetrics(metrics)) = &self.metrics {
+            f(&**metrics);
+        }
+    }
+
+    /// Check if stream ended at packet boundary.
+    /// Returns [SecureError::TruncatedFrame] if partial packet was read.
+    fn end_of_stream(&self, needed: usize) -> Result<(), SecureError> {
+        let got = self.read_buf.data().len();
+
+        if got == 0 {
+            Ok(())
+        } else {
+            Err(SecureError::TruncatedFrame {
+                expected: needed,
+                got,
+            })
+        }
+    }
 }
 
-impl<S: Read> SecureCodec<S> {
-    /// Read one encrypted packet
-    pub fn read_packet(&mut self) -> Result<SecurePacket, SecureError> {
-        let mut head_buf = [0_u8; SECURE_HEAD_SIZE];
-        self.stream.read_exact(&mut head_buf)?;
+impl<S: AsyncRead + AsyncWrite> SecureCodec<S> {
+    /// Split into owned read and write codec sharing same [CryptoStore].
+    /// Partially read or written packet moves to its half.
+    pub fn split(self) -> (SecureCodec<ReadHalf<S>>, SecureCodec<WriteHalf<S>>) {
+        let (read_stream, write_stream) = self.stream.split();
+
+        let read = SecureCodec {
+            crypto: self.crypto.clone(),
+            stream: read_stream,
+            read_buf: self.read_buf,
+            write_state: WriteState::new(),
+            metrics: self.metrics.clone(),
+        };
+
+        let write = SecureCodec {
+            crypto: self.crypto,
+            stream: write_stream,
+            read_buf: ReadAhead::new(0),
+            write_state: self.write_state,
+            metrics: self.metrics,
+        };
 
-        let mut packet = decode_secure_head(&head_buf)?;
-        self.stream.read_exact(&mut packet.data)?;
+        (read, write)
+    }
+}
 
-        let data = self.crypto.decrypt_aes(&packet.data, &packet.header.iv)?;
+impl<S: Unpin> SecureCodec<ReadHalf<S>> {
+    /// Reunite halves split by [SecureCodec::split]
+    #[allow(clippy::result_large_err)]
+    pub fn reunite(
+        self,
+        write: SecureCodec<WriteHalf<S>>,
+    ) -> Result<SecureCodec<S>, ReuniteError<Self, SecureCodec<WriteHalf<S>>>> {
+        if !self.stream.is_pair_of(&write.stream) {
+            return Err(ReuniteError(self, write));
+        }
 
-        Ok(SecurePacket {
-            header: packet.header,
-            data,
+        Ok(SecureCodec {
+            crypto: self.crypto,
+            stream: self.stream.reunite(write.stream).unwrap(),
+            read_buf: self.read_buf,
+            write_state: write.write_state,
+            metrics: self.metrics,
         })
     }
 }
 
+impl<S: Read> SecureCodec<S> {
+    /// Read one encrypted packet.
+    /// Returns [None] if stream ended cleanly before next packet.
+    pub fn read_packet(&mut self) -> Result<Option<SecurePacket>, SecureError> {
+        let mut data = Vec::new();
+        let header = self.read_packet_into(&mut data)?;
+
+        Ok(header.map(|header| SecurePacket { header, data }))
+    }
+
+    /// Read one encrypted packet and decrypt its data into buffer.
+    /// Buffer is cleared first and does not allocate if it has enough capacity.
+    /// Returns [None] if stream ended cleanly before next packet.
+    pub fn read_packet_into(
+        &mut self,
+        data: &mut Vec<u8>,
+    ) -> Result<Option<SecureHeader>, SecureError> {
+        loop {
+            let needed = match self.decode_packet(data)? {
+                Ok(header) => return Ok(Some(header)),
+                Err(needed) => needed,
+            };
+
+            let read = match self.stream.read(self.read_buf.spare(needed)) {
+                Ok(0) => return self.end_of_stream(needed).map(|_| None),
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            self.read_buf.fill(read);
+        }
+    }
+}
+
 impl<S: Write> SecureCodec<S> {
     /// Write one secure packet.
     /// Returns size of packet written.
     pub fn write_data(&mut self, buf: &[u8]) -> Result<usize, SecureError> {
-        let encrypted = to_encrypted_packet(&self.crypto, buf)?;
+        let packet = &mut self.write_state.packet;
+        encode_encrypted_packet(&self.crypto, buf, packet)?;
 
-        self.stream.write_all(&encrypted)?;
+        self.stream.write_all(packet)?;
+        let size = packet.len();
 
-        Ok(encrypted.len())
+        #[cfg(feature = "tracing")]
+        trace::packet_written(size);
+        self.report(|metrics| metrics.packet_written(size));
+
+        Ok(size)
     }
 }
 
 impl<S: AsyncRead + Unpin> SecureCodec<S> {
-    /// Read one encrypted packet
-    pub async fn read_packet_async(&mut self) -> Result<SecurePacket, SecureError> {
-        let mut head_buf = [0_u8; SECURE_HEAD_SIZE];
-        self.stream.read_exact(&mut head_buf).await?;
+    /// Read one encrypted packet.
+    /// Returns [None] if stream ended cleanly before next packet.
+    ///
+    /// # Cancel safety
+    /// This method is cancel safe.
+    /// Partially read packet is kept in codec and next call continues reading it,
+    /// so no bytes are lost or read twice if the future is dropped before completion.
+    pub async fn read_packet_async(&mut self) -> Result<Option<SecurePacket>, SecureError> {
+        poll_fn(|cx| self.poll_read_packet(cx)).await
+    }
 
-        let mut packet = decode_secure_head(&head_buf)?;
-        self.stream.read_exact(&mut packet.data).await?;
+    /// Read one encrypted packet and decrypt its data into buffer async.
+    /// See [SecureCodec::read_packet_into].
+    ///
+    /// # Cancel safety
+    /// This method is cancel safe. Buffer is only modified when packet completes.
+    pub async fn read_packet_into_async(
+        &mut self,
+        data: &mut Vec<u8>,
+    ) -> Result<Option<SecureHeader>, SecureError> {
+        poll_fn(|cx| self.poll_read_packet_into(cx, data)).await
+    }
 
-        let data = self.crypto.decrypt_aes(&packet.data, &packet.header.iv)?;
+    /// Poll one encrypted packet.
+    /// Partially read packet is kept in codec until it completes.
+    pub fn poll_read_packet(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<SecurePacket>, SecureError>> {
+        let mut data = Vec::new();
+        let header = ready!(self.poll_read_packet_into(cx, &mut data))?;
 
-        Ok(SecurePacket {
-            header: packet.header,
-            data,
-        })
+        Poll::Ready(Ok(header.map(|header| SecurePacket { header, data })))
+    }
+
+    /// Poll one encrypted packet decrypting its data into buffer.
+    /// Partially read packet is kept in codec until it completes.
+    pub fn poll_read_packet_into(
+        &mut self,
+        cx: &mut Context,
+        data: &mut Vec<u8>,
+    ) -> Poll<Result<Option<SecureHeader>, SecureError>> {
+        loop {
+            let needed = match self.decode_packet(data)? {
+                Ok(header) => return Poll::Ready(Ok(Some(header))),
+                Err(needed) => needed,
+            };
+
+            let buf = self.read_buf.spare(needed);
+            let read = ready!(Pin::new(&mut self.stream).poll_read(cx, buf))?;
+            if read == 0 {
+                return Poll::Ready(self.end_of_stream(needed).map(|_| None));
+            }
+
+            self.read_buf.fill(read);
+        }
     }
 }
 
 impl<S: AsyncWrite + Unpin> SecureCodec<S> {
     /// Write one secure packet.
     /// Returns size of packet written.
+    ///
+    /// Packet left partially written by canceled call is finished first.
     pub async fn write_data_async(&mut self, buf: &[u8]) -> Result<usize, SecureError> {
-        let encrypted = to_encrypted_packet(&self.crypto, buf)?;
+        poll_fn(|cx| self.poll_write_pending(cx)).await?;
 
-        self.stream.write_all(&encrypted).await?;
+        self.start_write(buf)?;
+        poll_fn(|cx| self.poll_write_pending(cx)).await
+    }
+
+    /// Encrypt data as next packet to write.
+    /// Poll [SecureCodec::poll_write_pending] to write it.
+    ///
+    /// # Panics
+    /// Panics if previous packet is not completely written yet.
+    pub fn start_write(&mut self, buf: &[u8]) -> Result<(), SecureError> {
+        let state = &mut self.write_state;
+        assert!(!state.pending, "previous packet must be written first");
+
+        encode_encrypted_packet(&self.crypto, buf, &mut state.packet)?;
+        state.written = 0;
+        state.pending = true;
 
-        Ok(encrypted.len())
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Returns true if packet started by [SecureCodec::start_write] is not completely written yet
+    pub const fn is_write_pending(&self) -> bool {
+        self.write_state.pending
+    }
+
+    /// Poll writing rest of partially written packet.
+    /// Returns size of packet written or 0 if there was no packet.
+    pub fn poll_write_pending(&mut self, cx: &mut Context) -> Poll<Result<usize, SecureError>> {
+        let state = &mut self.write_state;
+        if !state.pending {
+            return Poll::Ready(Ok(0));
+        }
+
+        while state.written < state.packet.len() {
+            let buf = &state.packet[state.written..];
+            let written = ready!(Pin::new(&mut self.stream).poll_write(cx, buf))?;
+
+            if written == 0 {
+                return Poll::Ready(Err(io::Error::from(io::ErrorKind::WriteZero).into()));
+            }
+
+            state.written += written;
+        }
+
+        state.pending = false;
+
+        let size = state.packet.len();
+
+        #[cfg(feature = "tracing")]
+        trace::packet_written(size);
+        self.report(|metrics| metrics.packet_written(size));
+
+        Poll::Ready(Ok(size))
+    }
+}
+
+/// Encrypted packet being written.
+/// Packet buffer is reused for next packet.
+#[derive(Debug)]
+struct WriteState {
+    packet: Vec<u8>,
+    written: usize,
+    pending: bool,
+}
+
+impl WriteState {
+    const fn new() -> Self {
+        Self {
+            packet: Vec::new(),
+            written: 0,
+            pending: false,
+        }
+    }
+}
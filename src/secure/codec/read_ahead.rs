@@ -0,0 +1,75 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+/// Read-ahead buffer of [SecureCodec](super::SecureCodec).
+/// Holds bytes read from stream but not decoded yet.
+#[derive(Debug)]
+pub struct ReadAhead {
+    buf: Vec<u8>,
+    start: usize,
+    end: usize,
+
+    capacity: usize,
+}
+
+impl ReadAhead {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            end: 0,
+            capacity,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Buffered bytes not consumed yet
+    pub fn data(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    /// Mark amt bytes as consumed
+    pub fn consume(&mut self, amt: usize) {
+        self.start = (self.start + amt).min(self.end);
+
+        if self.start == self.end {
+            self.start = 0;
+            self.end = 0;
+
+            // Release space grown for large packet
+            if self.buf.len() > self.capacity {
+                self.buf.truncate(self.capacity);
+                self.buf.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Returns free space to read into.
+    /// Buffer is grown so at least `needed` bytes of data fits in it.
+    pub fn spare(&mut self, needed: usize) -> &mut [u8] {
+        let size = needed.max(self.capacity);
+
+        if self.buf.len() - self.start < size && self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+
+        if self.buf.len() < self.start + size {
+            self.buf.resize(self.start + size, 0);
+        }
+
+        &mut self.buf[self.end..]
+    }
+
+    /// Mark amt bytes of spare space as filled
+    pub fn fill(&mut self, amt: usize) {
+        self.end = (self.end + amt).min(self.buf.len());
+    }
+}
@@ -4,12 +4,21 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use std::{error::Error, fmt::Display};
-
-use libaes::Cipher;
-use rand::{thread_rng, RngCore};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    sync::{Arc, Mutex},
+};
+
+use aes::{
+    cipher::{AsyncStreamCipher, InnerIvInit, KeyInit},
+    Aes128Enc,
+};
+use cfb_mode::{Decryptor, Encryptor};
+use rand::{thread_rng, CryptoRng, RngCore};
 use rsa::{PaddingScheme, PublicKey, RsaPublicKey};
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 #[repr(u32)]
 #[derive(Debug, Serialize, Deserialize, Copy, Clone)]
@@ -36,10 +45,46 @@ impl Display for CryptoError {
 
 impl Error for CryptoError {}
 
-/// AES Crypto implementation using aes
-#[derive(Debug, Clone)]
+/// Cryptographically secure random number generator usable by [CryptoStore]
+pub trait CryptoRngCore: RngCore + CryptoRng {}
+
+impl<T: RngCore + CryptoRng> CryptoRngCore for T {}
+
+/// Random number generator shared between clones of [CryptoStore]
+#[derive(Clone)]
+struct SharedRng(Arc<Mutex<dyn CryptoRngCore + Send>>);
+
+impl SharedRng {
+    fn fill_bytes(&self, data: &mut [u8]) {
+        self.with(|rng| rng.fill_bytes(data))
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut dyn CryptoRngCore) -> R) -> R {
+        let mut rng = self.0.lock().unwrap_or_else(|err| err.into_inner());
+
+        f(&mut *rng)
+    }
+}
+
+impl Debug for SharedRng {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedRng")
+    }
+}
+
+/// AES Crypto implementation using aes.
+///
+/// The AES key and its key schedule are zeroized on drop and redacted from [Debug] output.
+/// Wrap in [Unredacted] to print it.
+#[derive(Clone)]
 pub struct CryptoStore {
     aes_key: [u8; 16],
+
+    /// Key schedule of aes_key, expanded once. Zeroized on drop by aes.
+    cipher: Aes128Enc,
+
+    /// Injected rng. Uses [thread_rng] if [None]
+    rng: Option<SharedRng>,
 }
 
 impl CryptoStore {
@@ -50,38 +95,134 @@ impl CryptoStore {
 
         rng.fill_bytes(&mut aes_key);
 
-        Self { aes_key }
+        Self::new_with_key(aes_key)
+    }
+
+    /// Create new crypto store using given rng.
+    /// The AES key, packet ivs and RSA padding are all generated from it.
+    pub fn with_rng(rng: impl RngCore + CryptoRng + Send + 'static) -> Self {
+        let rng = SharedRng(Arc::new(Mutex::new(rng)));
+
+        let mut aes_key = [0_u8; 16];
+        rng.fill_bytes(&mut aes_key);
+
+        Self {
+            cipher: Aes128Enc::new(&aes_key.into()),
+            aes_key,
+            rng: Some(rng),
+        }
+    }
+
+    /// Create new crypto store using deterministic rng seeded with given seed.
+    ///
+    /// Never use it outside of tests. Every key and iv is predictable from the seed.
+    #[cfg(feature = "test-rng")]
+    pub fn new_seeded(seed: u64) -> Self {
+        use rand::SeedableRng;
+
+        Self::with_rng(rand_chacha::ChaCha20Rng::seed_from_u64(seed))
     }
 
     /// Create new crypto store using given AES key
     pub fn new_with_key(aes_key: [u8; 16]) -> Self {
-        Self { aes_key }
+        Self {
+            cipher: Aes128Enc::new(&aes_key.into()),
+            aes_key,
+            rng: None,
+        }
+    }
+
+    pub(crate) fn aes_key(&self) -> &[u8; 16] {
+        &self.aes_key
     }
 
     pub fn encrypt_aes(&self, data: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>, CryptoError> {
-        let cipher = Cipher::new_128(&self.aes_key);
+        let mut buf = data.to_vec();
+        self.encrypt_aes_in_place(&mut buf, iv)?;
 
-        Ok(cipher.cfb128_encrypt(iv, data))
+        Ok(buf)
     }
 
     pub fn decrypt_aes(&self, data: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>, CryptoError> {
-        let cipher = Cipher::new_128(&self.aes_key);
+        let mut buf = data.to_vec();
+        self.decrypt_aes_in_place(&mut buf, iv)?;
 
-        Ok(cipher.cfb128_decrypt(iv, data))
+        Ok(buf)
+    }
+
+    /// Encrypt data in place using AES-128 CFB128. Does not allocate.
+    pub fn encrypt_aes_in_place(&self, data: &mut [u8], iv: &[u8; 16]) -> Result<(), CryptoError> {
+        Encryptor::inner_iv_init(&self.cipher, iv.into()).encrypt(data);
+
+        Ok(())
+    }
+
+    /// Decrypt data in place using AES-128 CFB128. Does not allocate.
+    pub fn decrypt_aes_in_place(&self, data: &mut [u8], iv: &[u8; 16]) -> Result<(), CryptoError> {
+        Decryptor::inner_iv_init(&self.cipher, iv.into()).decrypt(data);
+
+        Ok(())
     }
 
     /// Encrypt AES key using RSA public key
     pub fn encrypt_key(&self, key: &RsaPublicKey) -> Result<Vec<u8>, CryptoError> {
-        Ok(key
-            .encrypt(
-                &mut thread_rng(),
-                PaddingScheme::new_oaep::<sha1::Sha1>(),
-                &self.aes_key,
-            )
-            .unwrap())
+        let padding = PaddingScheme::new_oaep::<sha1::Sha1>();
+
+        let encrypted = match &self.rng {
+            Some(rng) => rng.with(|mut rng| key.encrypt(&mut rng, padding, &self.aes_key)),
+            None => key.encrypt(&mut thread_rng(), padding, &self.aes_key),
+        };
+
+        Ok(encrypted.unwrap())
     }
 
     pub fn gen_random(&self, data: &mut [u8]) {
-        thread_rng().fill_bytes(data);
+        match &self.rng {
+            Some(rng) => rng.fill_bytes(data),
+            None => thread_rng().fill_bytes(data),
+        }
+    }
+}
+
+impl Debug for CryptoStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CryptoStore")
+            .field("aes_key", &Redacted)
+            .field("rng", &self.rng)
+            .finish()
+    }
+}
+
+impl Drop for CryptoStore {
+    fn drop(&mut self) {
+        self.aes_key.zeroize();
+    }
+}
+
+impl Default for CryptoStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Placeholder printed in place of secret in [Debug] output
+pub(crate) struct Redacted;
+
+impl Debug for Redacted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// Prints secrets redacted from [Debug] output of wrapped value, like `Unredacted(&crypto)`.
+/// Only for debugging.
+pub struct Unredacted<'a, T>(pub &'a T);
+
+impl Debug for Unredacted<'_, CryptoStore> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CryptoStore")
+            .field("aes_key", &self.0.aes_key)
+            .field("rng", &self.0.rng)
+            .finish()
     }
 }
@@ -19,6 +19,18 @@ pub struct SecureHeader {
     pub iv: [u8; 16],
 }
 
+impl SecureHeader {
+    /// Encode header to bytes in wire layout described in specification
+    pub fn to_bytes(&self) -> [u8; SECURE_HEADER_SIZE] {
+        self.iv
+    }
+
+    /// Decode header from bytes in wire layout described in specification
+    pub fn from_bytes(buf: &[u8; SECURE_HEADER_SIZE]) -> Self {
+        Self { iv: *buf }
+    }
+}
+
 #[derive(Debug)]
 pub struct SecurePacket {
     pub header: SecureHeader,
@@ -34,6 +46,26 @@ pub struct SecureHandshakeHeader {
     pub encrypt_type: u32,
 }
 
+impl SecureHandshakeHeader {
+    /// Encode header to bytes in wire layout described in specification
+    pub fn to_bytes(&self) -> [u8; SECURE_HANDSHAKE_HEADER_SIZE] {
+        let mut buf = [0_u8; SECURE_HANDSHAKE_HEADER_SIZE];
+
+        buf[..4].copy_from_slice(&self.key_encrypt_type.to_le_bytes());
+        buf[4..].copy_from_slice(&self.encrypt_type.to_le_bytes());
+
+        buf
+    }
+
+    /// Decode header from bytes in wire layout described in specification
+    pub fn from_bytes(buf: &[u8; SECURE_HANDSHAKE_HEADER_SIZE]) -> Self {
+        Self {
+            key_encrypt_type: u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            encrypt_type: u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecureHandshake {
     pub header: SecureHandshakeHeader,
@@ -11,6 +11,9 @@ use crate::secure::{
     SecureHandshakeHeader,
 };
 
+#[cfg(feature = "tracing")]
+use crate::trace;
+
 use super::SecureHandshakeError;
 
 pub fn to_handshake_packet(
@@ -23,12 +26,14 @@ pub fn to_handshake_packet(
         key_encrypt_type: KeyEncryptType::RsaOaepSha1Mgf1Sha1 as u32,
         encrypt_type: EncryptType::AesCfb128 as u32,
     };
-    let header_data = bincode::serialize(&handshake_header)?;
+
+    #[cfg(feature = "tracing")]
+    trace::handshake(&handshake_header, encrypted_key.len());
 
     Ok([
-        (encrypted_key.len() as u32).to_le_bytes().into(),
-        header_data,
-        encrypted_key,
+        &(encrypted_key.len() as u32).to_le_bytes()[..],
+        &handshake_header.to_bytes(),
+        &encrypted_key,
     ]
     .concat())
 }
@@ -0,0 +1,107 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+//! Opt-in session key logging for offline decryption of captured traffic.
+//!
+//! Each established key is written as one line.
+//!
+//! `LOCO_AES_KEY <connection id> <aes key>`
+//!
+//! Both fields are lowercase hex.
+//! The connection id is SHA-1 of encrypted key sent in handshake packet,
+//! so it can be computed again from captured handshake.
+//!
+//! Never enable it in production. Anyone reading the log can decrypt the traffic.
+
+use std::{
+    env,
+    fmt::{self, Write as _},
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use sha1::{Digest, Sha1};
+
+/// Environment variable read by [KeyLogFile::from_env]
+pub const KEY_LOG_FILE_ENV: &str = "LOCO_KEYLOGFILE";
+
+/// Label of key log line
+pub const KEY_LOG_LABEL: &str = "LOCO_AES_KEY";
+
+/// Receives session keys established by handshakes
+pub trait KeyLog: Send + Sync {
+    /// Called when handshake establishes a key.
+    /// `connection_id` is SHA-1 of encrypted key in handshake packet.
+    fn log(&self, connection_id: &[u8], key: &[u8]);
+}
+
+/// Compute connection id of handshake from encrypted key
+pub fn connection_id(encrypted_key: &[u8]) -> [u8; 20] {
+    Sha1::digest(encrypted_key).into()
+}
+
+/// Format one key log line without line ending
+pub fn format_key_log(connection_id: &[u8], key: &[u8]) -> String {
+    let mut line = String::with_capacity(
+        KEY_LOG_LABEL.len() + 2 + (connection_id.len() + key.len()) * 2,
+    );
+
+    line.push_str(KEY_LOG_LABEL);
+    line.push(' ');
+    write_hex(&mut line, connection_id);
+    line.push(' ');
+    write_hex(&mut line, key);
+
+    line
+}
+
+fn write_hex(line: &mut String, data: &[u8]) {
+    for byte in data {
+        let _ = write!(line, "{:02x}", byte);
+    }
+}
+
+/// [KeyLog] appending key log lines to file
+pub struct KeyLogFile {
+    file: Mutex<File>,
+}
+
+impl KeyLogFile {
+    /// Open file at path for appending
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Open file at path stored in `LOCO_KEYLOGFILE` environment variable.
+    /// Returns [None] if the variable is not set.
+    pub fn from_env() -> Option<io::Result<Self>> {
+        env::var_os(KEY_LOG_FILE_ENV).map(Self::new)
+    }
+}
+
+impl KeyLog for KeyLogFile {
+    fn log(&self, connection_id: &[u8], key: &[u8]) {
+        let mut line = format_key_log(connection_id, key);
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap_or_else(|err| err.into_inner());
+
+        // Key logging must not break the connection
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+impl fmt::Debug for KeyLogFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyLogFile").finish_non_exhaustive()
+    }
+}
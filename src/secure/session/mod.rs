@@ -5,38 +5,53 @@
  */
 
 pub mod client;
+pub mod key_log;
 pub mod server;
+pub mod shared;
 
-use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use rsa::{PaddingScheme, RsaPrivateKey, RsaPublicKey};
+use futures::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use rsa::{RsaPrivateKey, RsaPublicKey};
 
-use self::{client::to_handshake_packet, server::decode_handshake_head};
+use self::{
+    client::to_handshake_packet,
+    key_log::{connection_id, KeyLog},
+    server::{decrypt_handshake_key, read_handshake, read_handshake_async, require_handshake},
+};
 
 use super::{
-    crypto::{CryptoError, CryptoStore},
+    crypto::{CryptoError, CryptoStore, Redacted, Unredacted},
     stream::SecureStream,
 };
-use crate::secure::SECURE_HANDSHAKE_HEAD_SIZE;
+use crate::{
+    error::{is_retryable_io, ErrorKind, LocoError},
+    metrics::Metrics,
+    secure::{SecureHandshake, SECURE_HANDSHAKE_HEAD_SIZE},
+};
+
+#[cfg(feature = "tracing")]
+use crate::trace;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
 
 use std::{
-    convert::TryInto,
     error::Error,
-    fmt::Display,
+    fmt::{self, Display},
     io::{self, Read, Write},
+    sync::Arc,
+    time::Instant,
 };
 
 #[derive(Debug)]
 pub enum SecureHandshakeError {
-    Bincode(bincode::Error),
     Io(io::Error),
     Crypto(CryptoError),
     InvalidKey,
-}
 
-impl From<bincode::Error> for SecureHandshakeError {
-    fn from(err: bincode::Error) -> Self {
-        Self::Bincode(err)
-    }
+    /// Stream ended in middle of handshake
+    TruncatedFrame { expected: usize, got: usize },
+
+    /// Blocking task was dropped by executor before completion
+    Canceled,
 }
 
 impl From<io::Error> for SecureHandshakeError {
@@ -54,112 +69,233 @@ impl From<CryptoError> for SecureHandshakeError {
 impl Display for SecureHandshakeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            SecureHandshakeError::Bincode(err) => err.fmt(f),
-            SecureHandshakeError::Io(err) => err.fmt(f),
-            SecureHandshakeError::Crypto(err) => err.fmt(f),
+            SecureHandshakeError::Io(err) => write!(f, "Handshake IO error: {}", err),
+            SecureHandshakeError::Crypto(_) => write!(f, "Handshake key decryption failed"),
             SecureHandshakeError::InvalidKey => write!(f, "Invalid key"),
+            SecureHandshakeError::TruncatedFrame { expected, got } => write!(
+                f,
+                "Stream ended in middle of handshake. expected: {} bytes, got: {} bytes",
+                expected, got
+            ),
+            SecureHandshakeError::Canceled => write!(f, "Handshake task canceled"),
+        }
+    }
+}
+
+impl Error for SecureHandshakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SecureHandshakeError::Io(err) => Some(err),
+            SecureHandshakeError::Crypto(err) => Some(err),
+            _ => None,
         }
     }
 }
 
-impl Error for SecureHandshakeError {}
+impl SecureHandshakeError {
+    /// Kind of underlying failure.
+    /// Rejected key and canceled handshake are [ErrorKind::Handshake].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SecureHandshakeError::Io(_) => ErrorKind::Io,
+            SecureHandshakeError::Crypto(_) => ErrorKind::Crypto,
+            SecureHandshakeError::TruncatedFrame { .. } => ErrorKind::Framing,
+            SecureHandshakeError::InvalidKey | SecureHandshakeError::Canceled => {
+                ErrorKind::Handshake
+            }
+        }
+    }
+
+    /// Returns true if reconnecting may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SecureHandshakeError::Io(err) => is_retryable_io(err),
+            SecureHandshakeError::Crypto(_) | SecureHandshakeError::InvalidKey => false,
+            SecureHandshakeError::TruncatedFrame { .. } | SecureHandshakeError::Canceled => true,
+        }
+    }
+
+    /// Returns true if reconnecting with same configuration will fail again
+    pub fn is_fatal(&self) -> bool {
+        !self.is_retryable()
+    }
+}
 
 /// Client side credential session
-#[derive(Debug)]
 pub struct SecureClientSession {
     key: RsaPublicKey,
+    key_log: Option<Arc<dyn KeyLog>>,
 }
 
 impl SecureClientSession {
     pub const fn new(key: RsaPublicKey) -> Self {
-        Self { key }
+        Self { key, key_log: None }
+    }
+
+    /// Log every established session key to given [KeyLog]
+    pub fn with_key_log(mut self, key_log: Arc<dyn KeyLog>) -> Self {
+        self.key_log = Some(key_log);
+
+        self
+    }
+
+    fn log_key(&self, handshake: &[u8], crypto: &CryptoStore) {
+        if let Some(key_log) = &self.key_log {
+            key_log.log(
+                &connection_id(&handshake[SECURE_HANDSHAKE_HEAD_SIZE..]),
+                crypto.aes_key(),
+            );
+        }
+    }
+}
+
+impl fmt::Debug for SecureClientSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecureClientSession")
+            .field("key", &self.key)
+            .field("key_log", &self.key_log.is_some())
+            .finish()
     }
 }
 
 impl SecureClientSession {
-    /// Do client handshake
+    /// Do client handshake using given [CryptoStore] and returns ready to use [SecureStream]
     pub fn handshake<S: Write>(
         &self,
-        secure_stream: &mut SecureStream<S>,
-    ) -> Result<(), SecureHandshakeError> {
-        let handshake = to_handshake_packet(secure_stream.crypto(), &self.key)?;
+        crypto: CryptoStore,
+        mut stream: S,
+    ) -> Result<SecureStream<S>, LocoError> {
+        #[cfg(feature = "tracing")]
+        let _span = trace::handshake_span("client").entered();
+
+        let handshake = to_handshake_packet(&crypto, &self.key)?;
 
-        secure_stream.stream_mut().write_all(&handshake)?;
+        stream.write_all(&handshake)?;
+        self.log_key(&handshake, &crypto);
 
-        Ok(())
+        Ok(SecureStream::from_established(crypto, stream))
     }
 
-    /// Do client handshake async
-    pub async fn handshake_async<'a, S: AsyncWrite + Unpin>(
+    /// Do client handshake async using given [CryptoStore] and returns ready to use [SecureStream]
+    pub async fn handshake_async<S: AsyncWrite + Unpin>(
         &self,
-        secure_stream: &'a mut SecureStream<S>,
-    ) -> Result<(), SecureHandshakeError> {
-        let handshake = to_handshake_packet(secure_stream.crypto(), &self.key)?;
+        crypto: CryptoStore,
+        mut stream: S,
+    ) -> Result<SecureStream<S>, LocoError> {
+        let handshake = async {
+            let handshake = to_handshake_packet(&crypto, &self.key)?;
 
-        secure_stream.stream_mut().write_all(&handshake).await?;
+            stream.write_all(&handshake).await?;
+            self.log_key(&handshake, &crypto);
 
-        Ok(())
+            Ok::<_, SecureHandshakeError>(())
+        };
+
+        #[cfg(feature = "tracing")]
+        let handshake = handshake.instrument(trace::handshake_span("client"));
+
+        handshake.await?;
+
+        Ok(SecureStream::from_established(crypto, stream))
     }
 }
 
-/// Server side credential session
-#[derive(Debug)]
+/// Server side credential session.
+/// Private key is redacted from [fmt::Debug] output unless wrapped in [Unredacted].
 pub struct SecureServerSession {
     key: RsaPrivateKey,
+    key_log: Option<Arc<dyn KeyLog>>,
+    metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl fmt::Debug for SecureServerSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecureServerSession")
+            .field("key", &Redacted)
+            .field("key_log", &self.key_log.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Debug for Unredacted<'_, SecureServerSession> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecureServerSession")
+            .field("key", &self.0.key)
+            .field("key_log", &self.0.key_log.is_some())
+            .finish()
+    }
 }
 
 impl SecureServerSession {
     pub const fn new(key: RsaPrivateKey) -> Self {
-        Self { key }
-    }
-
-    /// Do server handshake and returns CryptoStore on success
-    pub fn handshake<S: Read>(
-        &mut self,
-        stream: &mut S,
-    ) -> Result<CryptoStore, SecureHandshakeError> {
-        let mut handshake_head_buf = [0_u8; SECURE_HANDSHAKE_HEAD_SIZE];
-        stream.read_exact(&mut handshake_head_buf)?;
-
-        let mut handshake = decode_handshake_head(&handshake_head_buf)?;
-        stream.read_exact(&mut handshake.encrypted_key)?;
-
-        let key = self
-            .key
-            .decrypt(
-                PaddingScheme::new_oaep::<sha1::Sha1>(),
-                &handshake.encrypted_key,
-            )
-            .map_err(|_| CryptoError::CorruptedData)?;
-
-        Ok(CryptoStore::new_with_key(
-            key.try_into()
-                .map_err(|_| SecureHandshakeError::InvalidKey)?,
-        ))
-    }
-
-    /// Do server handshake async and returns CryptoStore on success
-    pub async fn handshake_async<'a, S: AsyncRead + Unpin>(
-        &'a mut self,
-        stream: &'a mut S,
-    ) -> Result<CryptoStore, SecureHandshakeError> {
-        let mut handshake_head_buf = [0_u8; SECURE_HANDSHAKE_HEAD_SIZE];
-        stream.read_exact(&mut handshake_head_buf).await?;
-
-        let mut handshake = decode_handshake_head(&handshake_head_buf)?;
-        stream.read_exact(&mut handshake.encrypted_key).await?;
-
-        let key = self
-            .key
-            .decrypt(
-                PaddingScheme::new_oaep::<sha1::Sha1>(),
-                &handshake.encrypted_key,
-            )
-            .map_err(|_| CryptoError::CorruptedData)?;
-
-        Ok(CryptoStore::new_with_key(
-            key.try_into()
-                .map_err(|_| SecureHandshakeError::InvalidKey)?,
-        ))
+        Self {
+            key,
+            key_log: None,
+            metrics: None,
+        }
+    }
+
+    /// Log every established session key to given [KeyLog]
+    pub fn with_key_log(mut self, key_log: Arc<dyn KeyLog>) -> Self {
+        self.key_log = Some(key_log);
+
+        self
+    }
+
+    /// Report handshake duration to given [Metrics]
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+
+        self
+    }
+
+    fn log_key(&self, encrypted_key: &[u8], crypto: &CryptoStore) {
+        if let Some(key_log) = &self.key_log {
+            key_log.log(&connection_id(encrypted_key), crypto.aes_key());
+        }
+    }
+
+    /// Decrypt key of received handshake and report its duration
+    fn complete(&self, handshake: &SecureHandshake) -> Result<CryptoStore, SecureHandshakeError> {
+        let started = Instant::now();
+
+        let crypto = decrypt_handshake_key(&self.key, &handshake.encrypted_key)?;
+        self.log_key(&handshake.encrypted_key, &crypto);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.handshake(started.elapsed());
+        }
+
+        Ok(crypto)
+    }
+
+    /// Do server handshake and returns ready to use [SecureStream]
+    pub fn handshake<S: Read>(&self, mut stream: S) -> Result<SecureStream<S>, LocoError> {
+        #[cfg(feature = "tracing")]
+        let _span = trace::handshake_span("server").entered();
+
+        let handshake = require_handshake(read_handshake(&mut stream)?)?;
+        let crypto = self.complete(&handshake)?;
+
+        Ok(SecureStream::from_established(crypto, stream))
+    }
+
+    /// Do server handshake async and returns ready to use [SecureStream]
+    pub async fn handshake_async<S: AsyncRead + Unpin>(
+        &self,
+        mut stream: S,
+    ) -> Result<SecureStream<S>, LocoError> {
+        let handshake = async {
+            let handshake = require_handshake(read_handshake_async(&mut stream).await?)?;
+
+            self.complete(&handshake)
+        };
+
+        #[cfg(feature = "tracing")]
+        let handshake = handshake.instrument(trace::handshake_span("server"));
+
+        let crypto = handshake.await?;
+
+        Ok(SecureStream::from_established(crypto, stream))
     }
 }
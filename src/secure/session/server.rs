@@ -4,22 +4,140 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use std::io::Cursor;
+use std::{
+    convert::TryInto,
+    io::{self, Read},
+};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use futures::AsyncRead;
+use rsa::{PaddingScheme, RsaPrivateKey};
+use zeroize::Zeroizing;
 
-use crate::secure::{SecureHandshake, SecureHandshakeHeader, SECURE_HANDSHAKE_HEAD_SIZE};
+use crate::{
+    io_util::{read_full, read_full_async},
+    secure::{
+        crypto::{CryptoError, CryptoStore},
+        SecureHandshake, SecureHandshakeHeader, SECURE_HANDSHAKE_HEAD_SIZE,
+    },
+};
+
+#[cfg(feature = "tracing")]
+use crate::trace;
 
 use super::SecureHandshakeError;
 
 /// Decode key_size and [SecureHandshakeHeader] into empty [SecureHandshake].
-pub fn decode_handshake_head(buf: &[u8]) -> Result<SecureHandshake, SecureHandshakeError> {
-    let key_size = Cursor::new(&buf[..4]).read_u32::<LittleEndian>()?;
+pub fn decode_handshake_head(buf: &[u8]) -> SecureHandshake {
+    let key_size = u32::from_le_bytes(buf[..4].try_into().unwrap());
     let header =
-        bincode::deserialize::<SecureHandshakeHeader>(&buf[4..SECURE_HANDSHAKE_HEAD_SIZE])?;
+        SecureHandshakeHeader::from_bytes(buf[4..SECURE_HANDSHAKE_HEAD_SIZE].try_into().unwrap());
+
+    #[cfg(feature = "tracing")]
+    trace::handshake(&header, key_size as usize);
 
-    Ok(SecureHandshake {
+    SecureHandshake {
         header,
         encrypted_key: vec![0_u8; key_size as usize],
-    })
+    }
+}
+
+/// Read one [SecureHandshake] from stream.
+/// Returns [None] if stream ended before handshake.
+pub fn read_handshake<S: Read>(
+    stream: &mut S,
+) -> Result<Option<SecureHandshake>, SecureHandshakeError> {
+    let mut handshake_head_buf = [0_u8; SECURE_HANDSHAKE_HEAD_SIZE];
+    let read = read_full(stream, &mut handshake_head_buf)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    check_head_read(read)?;
+
+    let mut handshake = decode_handshake_head(&handshake_head_buf);
+    let read = read_full(stream, &mut handshake.encrypted_key)?;
+    check_key_read(&handshake, read)?;
+
+    Ok(Some(handshake))
+}
+
+/// Read one [SecureHandshake] from stream async.
+/// Returns [None] if stream ended before handshake.
+pub async fn read_handshake_async<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> Result<Option<SecureHandshake>, SecureHandshakeError> {
+    let mut handshake_head_buf = [0_u8; SECURE_HANDSHAKE_HEAD_SIZE];
+    let read = read_full_async(stream, &mut handshake_head_buf).await?;
+    if read == 0 {
+        return Ok(None);
+    }
+    check_head_read(read)?;
+
+    let mut handshake = decode_handshake_head(&handshake_head_buf);
+    let read = read_full_async(stream, &mut handshake.encrypted_key).await?;
+    check_key_read(&handshake, read)?;
+
+    Ok(Some(handshake))
+}
+
+fn check_head_read(read: usize) -> Result<(), SecureHandshakeError> {
+    if read < SECURE_HANDSHAKE_HEAD_SIZE {
+        return Err(SecureHandshakeError::TruncatedFrame {
+            expected: SECURE_HANDSHAKE_HEAD_SIZE,
+            got: read,
+        });
+    }
+
+    Ok(())
+}
+
+fn check_key_read(handshake: &SecureHandshake, read: usize) -> Result<(), SecureHandshakeError> {
+    if read < handshake.encrypted_key.len() {
+        return Err(SecureHandshakeError::TruncatedFrame {
+            expected: SECURE_HANDSHAKE_HEAD_SIZE + handshake.encrypted_key.len(),
+            got: SECURE_HANDSHAKE_HEAD_SIZE + read,
+        });
+    }
+
+    Ok(())
+}
+
+/// Unwrap handshake read by [read_handshake] for server handshake,
+/// which requires it. Stream ended before handshake is [io::ErrorKind::UnexpectedEof].
+pub(crate) fn require_handshake(
+    handshake: Option<SecureHandshake>,
+) -> Result<SecureHandshake, SecureHandshakeError> {
+    handshake.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+}
+
+/// Decrypt AES key sent by client using RSA private key.
+/// This is the expensive private key operation of server handshake.
+pub fn decrypt_handshake_key(
+    key: &RsaPrivateKey,
+    encrypted_key: &[u8],
+) -> Result<CryptoStore, SecureHandshakeError> {
+    let res = decrypt_key(key, encrypted_key);
+
+    #[cfg(feature = "tracing")]
+    if let Err(err) = &res {
+        trace::handshake_failed(err);
+    }
+
+    res
+}
+
+fn decrypt_key(
+    key: &RsaPrivateKey,
+    encrypted_key: &[u8],
+) -> Result<CryptoStore, SecureHandshakeError> {
+    let aes_key = Zeroizing::new(
+        key.decrypt(PaddingScheme::new_oaep::<sha1::Sha1>(), encrypted_key)
+            .map_err(|_| CryptoError::CorruptedData)?,
+    );
+
+    Ok(CryptoStore::new_with_key(
+        aes_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| SecureHandshakeError::InvalidKey)?,
+    ))
 }
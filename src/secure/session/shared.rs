@@ -0,0 +1,331 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Instant,
+};
+
+use futures::{channel::oneshot, AsyncRead};
+use rsa::RsaPrivateKey;
+
+use crate::{
+    error::LocoError,
+    metrics::Metrics,
+    secure::{
+        crypto::{Redacted, Unredacted},
+        stream::SecureStream,
+    },
+};
+
+#[cfg(feature = "tracing")]
+use crate::trace;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
+
+use super::{
+    key_log::{connection_id, KeyLog},
+    server::{decrypt_handshake_key, read_handshake_async, require_handshake},
+    SecureHandshakeError,
+};
+
+/// Runs blocking tasks outside of async executor threads
+pub trait BlockingExecutor: Send + Sync {
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>);
+}
+
+/// [BlockingExecutor] spawning new thread for every task
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadExecutor;
+
+impl BlockingExecutor for ThreadExecutor {
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) {
+        thread::spawn(task);
+    }
+}
+
+/// Server side credential session which can be shared between connections.
+///
+/// RSA private key operation runs on [BlockingExecutor] instead of async executor.
+/// At most `max_handshakes` private key operations run at once, others wait for their turn.
+#[derive(Clone)]
+pub struct SharedServerSession {
+    key: Arc<RsaPrivateKey>,
+    executor: Arc<dyn BlockingExecutor>,
+    limiter: Arc<Limiter>,
+    key_log: Option<Arc<dyn KeyLog>>,
+    metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl SharedServerSession {
+    /// Create new session.
+    ///
+    /// # Panics
+    /// Panics if `max_handshakes` is zero.
+    pub fn new(
+        key: RsaPrivateKey,
+        executor: Arc<dyn BlockingExecutor>,
+        max_handshakes: usize,
+    ) -> Self {
+        assert!(
+            max_handshakes > 0,
+            "max_handshakes must be greater than zero"
+        );
+
+        Self {
+            key: Arc::new(key),
+            executor,
+            limiter: Arc::new(Limiter::new(max_handshakes)),
+            key_log: None,
+            metrics: None,
+        }
+    }
+
+    /// Log every established session key to given [KeyLog]
+    pub fn with_key_log(mut self, key_log: Arc<dyn KeyLog>) -> Self {
+        self.key_log = Some(key_log);
+
+        self
+    }
+
+    /// Report handshake duration, including wait for free handshake slot, to given [Metrics]
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+
+        self
+    }
+
+    /// Do server handshake async and returns ready to use [SecureStream]
+    pub async fn handshake_async<S: AsyncRead + Unpin>(
+        &self,
+        mut stream: S,
+    ) -> Result<SecureStream<S>, LocoError> {
+        let handshake = async {
+            let handshake = require_handshake(read_handshake_async(&mut stream).await?)?;
+            let started = Instant::now();
+
+            let permit = self.limiter.acquire().await;
+
+            let (sender, receiver) = oneshot::channel();
+            let key = self.key.clone();
+            let encrypted_key = handshake.encrypted_key;
+            self.executor.spawn_blocking(Box::new(move || {
+                let res = decrypt_handshake_key(&key, &encrypted_key)
+                    .map(|crypto| (crypto, encrypted_key));
+
+                // Permit is held until decrypt finishes even if handshake was canceled
+                drop(permit);
+                let _ = sender.send(res);
+            }));
+
+            let (crypto, encrypted_key) = receiver
+                .await
+                .map_err(|_| SecureHandshakeError::Canceled)??;
+
+            if let Some(key_log) = &self.key_log {
+                key_log.log(&connection_id(&encrypted_key), crypto.aes_key());
+            }
+
+            if let Some(metrics) = &self.metrics {
+                metrics.handshake(started.elapsed());
+            }
+
+            Ok::<_, SecureHandshakeError>(crypto)
+        };
+
+        #[cfg(feature = "tracing")]
+        let handshake = handshake.instrument(trace::handshake_span("server"));
+
+        let crypto = handshake.await?;
+
+        Ok(SecureStream::from_established(crypto, stream))
+    }
+}
+
+impl fmt::Debug for SharedServerSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedServerSession")
+            .field("key", &Redacted)
+            .field("limiter", &self.limiter)
+            .field("key_log", &self.key_log.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Debug for Unredacted<'_, SharedServerSession> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedServerSession")
+            .field("key", &self.0.key)
+            .field("limiter", &self.0.limiter)
+            .field("key_log", &self.0.key_log.is_some())
+            .finish()
+    }
+}
+
+/// Async counting semaphore bounding concurrent handshakes.
+/// Waiters get permits in order of arrival.
+#[derive(Debug)]
+struct Limiter {
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    permits: usize,
+    next_id: u64,
+    waiters: VecDeque<Waiter>,
+}
+
+impl LimiterState {
+    /// Waker of first waiter in line if a permit is available
+    fn next_waker(&self) -> Option<Waker> {
+        if self.permits > 0 {
+            self.waiters.front().map(|waiter| waiter.waker.clone())
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Waiter {
+    id: u64,
+    waker: Waker,
+}
+
+impl Limiter {
+    fn new(permits: usize) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                permits,
+                next_id: 0,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Acquire permit which can be moved to other thread
+    fn acquire(self: &Arc<Self>) -> Acquire {
+        Acquire {
+            limiter: self.clone(),
+            id: None,
+        }
+    }
+
+    fn release(&self) {
+        let waker = {
+            let mut state = self.lock();
+            state.permits += 1;
+
+            state.next_waker()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+struct Acquire {
+    limiter: Arc<Limiter>,
+
+    /// Id in waiter queue if waiting
+    id: Option<u64>,
+}
+
+impl Future for Acquire {
+    type Output = Permit;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut state = this.limiter.lock();
+
+        match this.id {
+            Some(id) => {
+                if state.permits > 0 && state.waiters.front().map(|waiter| waiter.id) == Some(id) {
+                    state.waiters.pop_front();
+                } else {
+                    if let Some(waiter) = state.waiters.iter_mut().find(|waiter| waiter.id == id) {
+                        if !waiter.waker.will_wake(cx.waker()) {
+                            waiter.waker = cx.waker().clone();
+                        }
+                    }
+
+                    return Poll::Pending;
+                }
+            }
+
+            None => {
+                if state.permits == 0 || !state.waiters.is_empty() {
+                    let id = state.next_id;
+                    state.next_id += 1;
+                    state.waiters.push_back(Waiter {
+                        id,
+                        waker: cx.waker().clone(),
+                    });
+                    this.id = Some(id);
+
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        state.permits -= 1;
+        this.id = None;
+
+        // Let next waiter take remaining permit
+        let waker = state.next_waker();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        Poll::Ready(Permit {
+            limiter: this.limiter.clone(),
+        })
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        let id = match self.id {
+            Some(id) => id,
+            None => return,
+        };
+
+        let waker = {
+            let mut state = self.limiter.lock();
+            state.waiters.retain(|waiter| waiter.id != id);
+
+            state.next_waker()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Owned permit released on drop
+struct Permit {
+    limiter: Arc<Limiter>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.limiter.release();
+    }
+}
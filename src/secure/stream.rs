@@ -5,35 +5,120 @@
  */
 
 use std::{
-    io::{self, Read, Write},
+    io::{self, BufRead, IoSlice, Read, Write},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
-use futures::{pin_mut, ready, AsyncRead, AsyncWrite, FutureExt};
+use futures::{
+    io::{ReadHalf, WriteHalf},
+    ready, AsyncBufRead, AsyncRead, AsyncWrite, AsyncWriteExt,
+};
+use rsa::{RsaPrivateKey, RsaPublicKey};
 
-use crate::vec_buf::VecBuf;
+use crate::{error::LocoError, metrics::Metrics, split::ReuniteError, read_buf::ReadBuf};
 
 use super::{
     codec::{SecureCodec, SecureError},
     crypto::CryptoStore,
+    session::{
+        client::to_handshake_packet,
+        server::{decrypt_handshake_key, read_handshake, read_handshake_async, require_handshake},
+    },
 };
 
-/// Secure layer used in client and server
+/// Default maximum size of data in one packet written by [SecureStream]
+pub const DEFAULT_MAX_PACKET_SIZE: usize = 64 * 1024;
+
+/// Secure layer used in client and server.
+///
+/// Use [SecureStream::connect] or [SecureStream::accept] to handshake on raw stream.
+/// Handshake is done only once and a stream is ready to use as soon as it exists.
+///
+/// Written data is buffered and sent as one secure packet on flush.
+/// Packets are at most max packet size and full packets are sent as soon as the buffer fills up.
+/// Unflushed data is discarded on drop.
 #[derive(Debug)]
 pub struct SecureStream<S> {
     codec: SecureCodec<S>,
-    read_buf: VecBuf,
+    read_buf: ReadBuf,
+
+    write_buf: Vec<u8>,
+    max_packet_size: Option<usize>,
 }
 
 impl<S> SecureStream<S> {
-    pub fn new(crypto: CryptoStore, stream: S) -> Self {
+    /// Create secure stream on raw stream whose handshake is already done using `crypto`.
+    /// Nothing is sent or checked, so both peers must already share same key.
+    ///
+    /// Use [SecureStream::connect] or [SecureStream::accept] to handshake first.
+    pub fn from_established(crypto: CryptoStore, stream: S) -> Self {
         Self {
             codec: SecureCodec::new(crypto, stream),
-            read_buf: VecBuf::new(),
+            read_buf: ReadBuf::new(),
+            write_buf: Vec::new(),
+            max_packet_size: Some(DEFAULT_MAX_PACKET_SIZE),
         }
     }
 
+    /// Read ahead up to `size` bytes at once.
+    /// See [SecureCodec::with_read_buffer_size].
+    pub fn with_read_buffer_size(mut self, size: usize) -> Self {
+        self.codec.set_read_buffer_size(size);
+
+        self
+    }
+
+    /// Report packets and decryption failures to given [Metrics]
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.codec = self.codec.with_metrics(metrics);
+
+        self
+    }
+
+    /// Maximum size of data in one secure packet. [None] if unlimited.
+    /// Defaults to [DEFAULT_MAX_PACKET_SIZE].
+    pub const fn max_packet_size(&self) -> Option<usize> {
+        self.max_packet_size
+    }
+
+    /// Set maximum size of data in one secure packet.
+    ///
+    /// # Panics
+    /// Panics if size is zero.
+    pub fn set_max_packet_size(&mut self, size: Option<usize>) {
+        assert!(size != Some(0), "max packet size must be greater than zero");
+
+        self.max_packet_size = size;
+    }
+
+    /// Size of remaining buffer space before reaching max packet size
+    fn write_buf_left(&self) -> usize {
+        match self.max_packet_size {
+            Some(size) => size.saturating_sub(self.write_buf.len()),
+            None => usize::MAX,
+        }
+    }
+
+    /// Append as many bytes of bufs as fit in current packet.
+    /// Returns size of bytes buffered.
+    fn buffer_vectored(&mut self, bufs: &[IoSlice]) -> usize {
+        let mut total = 0;
+
+        for buf in bufs {
+            let len = buf.len().min(self.write_buf_left());
+            self.write_buf.extend_from_slice(&buf[..len]);
+            total += len;
+
+            if len < buf.len() {
+                break;
+            }
+        }
+
+        total
+    }
+
     pub fn stream(&self) -> &S {
         self.codec.stream()
     }
@@ -46,31 +131,212 @@ impl<S> SecureStream<S> {
         self.codec.crypto()
     }
 
+    /// Unwrap inner stream. Unflushed data is discarded.
     pub fn into_inner(self) -> (CryptoStore, S) {
         self.codec.into_inner()
     }
 }
 
+/// Owned read half of [SecureStream]
+pub type SecureReadHalf<S> = SecureStream<ReadHalf<S>>;
+
+/// Owned write half of [SecureStream]
+pub type SecureWriteHalf<S> = SecureStream<WriteHalf<S>>;
+
+impl<S: AsyncRead + AsyncWrite> SecureStream<S> {
+    /// Split into owned read and write half sharing same [CryptoStore].
+    /// Each half can be used from different task concurrently.
+    pub fn split(self) -> (SecureReadHalf<S>, SecureWriteHalf<S>) {
+        let (read_codec, write_codec) = self.codec.split();
+
+        let read = SecureStream {
+            codec: read_codec,
+            read_buf: self.read_buf,
+            write_buf: Vec::new(),
+            max_packet_size: self.max_packet_size,
+        };
+
+        let write = SecureStream {
+            codec: write_codec,
+            read_buf: ReadBuf::new(),
+            write_buf: self.write_buf,
+            max_packet_size: self.max_packet_size,
+        };
+
+        (read, write)
+    }
+}
+
+impl<S: Unpin> SecureReadHalf<S> {
+    /// Reunite halves split by [SecureStream::split]
+    #[allow(clippy::result_large_err)]
+    pub fn reunite(
+        self,
+        write: SecureWriteHalf<S>,
+    ) -> Result<SecureStream<S>, ReuniteError<Self, SecureWriteHalf<S>>> {
+        let SecureStream {
+            codec,
+            write_buf,
+            max_packet_size,
+            ..
+        } = write;
+
+        match self.codec.reunite(codec) {
+            Ok(codec) => Ok(SecureStream {
+                codec,
+                read_buf: self.read_buf,
+                write_buf,
+                max_packet_size,
+            }),
+
+            Err(ReuniteError(read_codec, write_codec)) => Err(ReuniteError(
+                SecureStream {
+                    codec: read_codec,
+                    ..self
+                },
+                SecureStream {
+                    codec: write_codec,
+                    read_buf: ReadBuf::new(),
+                    write_buf,
+                    max_packet_size,
+                },
+            )),
+        }
+    }
+}
+
+impl<S: Write> SecureStream<S> {
+    /// Do client handshake on raw stream using new random key.
+    ///
+    /// Session key is not written to any [KeyLog](super::session::key_log::KeyLog).
+    /// Use [SecureClientSession](super::session::SecureClientSession) to log it.
+    pub fn connect(mut stream: S, key: &RsaPublicKey) -> Result<Self, LocoError> {
+        let crypto = CryptoStore::new();
+
+        stream.write_all(&to_handshake_packet(&crypto, key)?)?;
+
+        Ok(Self::from_established(crypto, stream))
+    }
+}
+
+impl<S: Read> SecureStream<S> {
+    /// Do server handshake on raw stream.
+    ///
+    /// Session key is not written to any [KeyLog](super::session::key_log::KeyLog).
+    /// Use [SecureServerSession](super::session::SecureServerSession) to log it.
+    pub fn accept(mut stream: S, key: &RsaPrivateKey) -> Result<Self, LocoError> {
+        let handshake = require_handshake(read_handshake(&mut stream)?)?;
+        let crypto = decrypt_handshake_key(key, &handshake.encrypted_key)?;
+
+        Ok(Self::from_established(crypto, stream))
+    }
+}
+
+impl<S: AsyncWrite + Unpin> SecureStream<S> {
+    /// Do client handshake on raw stream async using new random key.
+    ///
+    /// Session key is not written to any [KeyLog](super::session::key_log::KeyLog).
+    /// Use [SecureClientSession](super::session::SecureClientSession) to log it.
+    pub async fn connect_async(
+        mut stream: S,
+        key: &RsaPublicKey,
+    ) -> Result<Self, LocoError> {
+        let crypto = CryptoStore::new();
+
+        stream.write_all(&to_handshake_packet(&crypto, key)?).await?;
+
+        Ok(Self::from_established(crypto, stream))
+    }
+}
+
+impl<S: AsyncRead + Unpin> SecureStream<S> {
+    /// Do server handshake on raw stream async.
+    ///
+    /// RSA private key decryption runs inline and blocks current task for milliseconds.
+    /// Use [SharedServerSession](super::session::shared::SharedServerSession)
+    /// to run it on [BlockingExecutor](super::session::shared::BlockingExecutor) instead.
+    ///
+    /// Session key is not written to any [KeyLog](super::session::key_log::KeyLog).
+    pub async fn accept_async(
+        mut stream: S,
+        key: &RsaPrivateKey,
+    ) -> Result<Self, LocoError> {
+        let handshake = require_handshake(read_handshake_async(&mut stream).await?)?;
+        let crypto = decrypt_handshake_key(key, &handshake.encrypted_key)?;
+
+        Ok(Self::from_established(crypto, stream))
+    }
+}
+
 impl<S: Read> Read for SecureStream<S> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if self.read_buf.is_empty() {
-            let chunk = self.codec.read_packet().map_err(io_error_map)?;
+        self.fill_buf()?;
+
+        self.read_buf.read(buf)
+    }
+}
 
-            self.read_buf.push(chunk.data);
+impl<S: Read> BufRead for SecureStream<S> {
+    /// Returns unread data of current decrypted packet, reading next packet if it is empty.
+    /// Returns empty slice if stream ended at packet boundary.
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.read_buf.is_empty() {
+            let header = self
+                .codec
+                .read_packet_into(self.read_buf.reuse())
+                .map_err(io_error_map)?;
+
+            if header.is_none() {
+                break;
+            }
         }
 
-        self.read_buf.read(buf)
+        Ok(self.read_buf.as_slice())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.read_buf.consume(amt)
+    }
+}
+
+impl<S: Write> SecureStream<S> {
+    /// Send buffered data as one secure packet
+    fn write_packet(&mut self) -> io::Result<()> {
+        if !self.write_buf.is_empty() {
+            self.codec
+                .write_data(&self.write_buf)
+                .map_err(io_error_map)?;
+
+            self.write_buf.clear();
+        }
+
+        Ok(())
     }
 }
 
 impl<S: Write> Write for SecureStream<S> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.codec.write_data(buf).map_err(io_error_map)?;
+        if self.write_buf_left() == 0 {
+            self.write_packet()?;
+        }
 
-        Ok(buf.len())
+        let len = buf.len().min(self.write_buf_left());
+        self.write_buf.extend_from_slice(&buf[..len]);
+
+        Ok(len)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        if self.write_buf_left() == 0 {
+            self.write_packet()?;
+        }
+
+        Ok(self.buffer_vectored(bufs))
     }
 
     fn flush(&mut self) -> io::Result<()> {
+        self.write_packet()?;
+
         self.codec.stream_mut().flush()
     }
 }
@@ -81,48 +347,107 @@ impl<S: AsyncRead + Unpin> AsyncRead for SecureStream<S> {
         cx: &mut Context,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        if self.read_buf.is_empty() {
-            let chunk = {
-                let fut = self.codec.read_packet_async();
-                pin_mut!(fut);
-                ready!(fut.poll_unpin(cx).map_err(io_error_map)?)
-            };
-
-            self.read_buf.push(chunk.data);
-        }
+        ready!(self.as_mut().poll_fill_buf(cx))?;
 
         Poll::Ready(self.read_buf.read(buf))
     }
 }
 
+impl<S: AsyncRead + Unpin> AsyncBufRead for SecureStream<S> {
+    /// Returns unread data of current decrypted packet, reading next packet if it is empty.
+    /// Returns empty slice if stream ended at packet boundary.
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        while this.read_buf.is_empty() {
+            let header = ready!(this
+                .codec
+                .poll_read_packet_into(cx, this.read_buf.reuse())
+                .map_err(io_error_map))?;
+
+            if header.is_none() {
+                break;
+            }
+        }
+
+        Poll::Ready(Ok(this.read_buf.as_slice()))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.read_buf.consume(amt)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> SecureStream<S> {
+    /// Poll sending buffered data as one secure packet
+    fn poll_write_packet(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        loop {
+            ready!(self.codec.poll_write_pending(cx).map_err(io_error_map))?;
+
+            if self.write_buf.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Data belongs to pending packet of codec from now
+            self.codec
+                .start_write(&self.write_buf)
+                .map_err(io_error_map)?;
+            self.write_buf.clear();
+        }
+    }
+}
+
 impl<S: AsyncWrite + Unpin> AsyncWrite for SecureStream<S> {
     fn poll_write(
         mut self: Pin<&mut Self>,
         cx: &mut Context,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        {
-            let fut = self.codec.write_data_async(&buf);
-            pin_mut!(fut);
-            ready!(fut.poll_unpin(cx).map_err(io_error_map))?;
-        };
+        // Finish previous packet first, so buffered data never exceeds one packet
+        ready!(self.codec.poll_write_pending(cx).map_err(io_error_map))?;
+        if self.write_buf_left() == 0 {
+            ready!(self.poll_write_packet(cx))?;
+        }
 
-        Poll::Ready(Ok(buf.len()))
+        let len = buf.len().min(self.write_buf_left());
+        self.write_buf.extend_from_slice(&buf[..len]);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.codec.poll_write_pending(cx).map_err(io_error_map))?;
+        if self.write_buf_left() == 0 {
+            ready!(self.poll_write_packet(cx))?;
+        }
+
+        Poll::Ready(Ok(self.buffer_vectored(bufs)))
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_packet(cx))?;
+
         Pin::new(self.codec.stream_mut()).poll_flush(cx)
     }
 
     fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_packet(cx))?;
+
         Pin::new(self.codec.stream_mut()).poll_close(cx)
     }
 }
 
+/// Convert to [io::Error] keeping [SecureError] as its inner error
 fn io_error_map(err: SecureError) -> io::Error {
     match err {
         SecureError::Io(err) => err,
 
-        _ => io::Error::new(io::ErrorKind::InvalidData, "Invalid encryption data"),
+        SecureError::TruncatedFrame { .. } => io::Error::new(io::ErrorKind::UnexpectedEof, err),
+
+        SecureError::Crypto(_) => io::Error::new(io::ErrorKind::InvalidData, err),
     }
 }
@@ -0,0 +1,28 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+};
+
+/// Error returned when trying to reunite halves which came from different split.
+/// Contains both halves given.
+pub struct ReuniteError<R, W>(pub R, pub W);
+
+impl<R, W> Debug for ReuniteError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ReuniteError(..)")
+    }
+}
+
+impl<R, W> Display for ReuniteError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tried to reunite halves that are not from the same split")
+    }
+}
+
+impl<R, W> Error for ReuniteError<R, W> {}
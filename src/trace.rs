@@ -0,0 +1,120 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+//! `tracing` instrumentation.
+//! Command bodies are recorded only by codecs opted in using `CommandCodec::with_body_tracing`.
+//! Key material is never recorded. Use `KeyLog` to export session keys.
+
+use std::fmt::{self, Display};
+
+use tracing::{debug, debug_span, field, Span};
+
+use crate::command::Header;
+
+#[cfg(feature = "secure")]
+use crate::secure::SecureHandshakeHeader;
+
+/// Displays method field without allocation
+struct Method<'a>(&'a [u8; 11]);
+
+impl Display for Method<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.0.iter().position(|&c| c == b'\0').unwrap_or(11);
+
+        match std::str::from_utf8(&self.0[..size]) {
+            Ok(method) => f.write_str(method),
+            Err(_) => write!(f, "{:?}", &self.0[..size]),
+        }
+    }
+}
+
+/// Span covering write of one command
+pub(crate) fn command_write_span(header: &Header, size: usize) -> Span {
+    debug_span!(
+        "command_write",
+        id = header.id,
+        method = %Method(&header.method),
+        status = header.status,
+        data_type = header.data_type,
+        size,
+    )
+}
+
+pub(crate) fn command_written(size: usize) {
+    debug!(size, "command written");
+}
+
+/// Span covering read of one command.
+/// Fields are recorded by [command_read_head] once head is read.
+pub(crate) fn command_read_span() -> Span {
+    debug_span!(
+        "command_read",
+        id = field::Empty,
+        method = field::Empty,
+        status = field::Empty,
+        data_type = field::Empty,
+        size = field::Empty,
+    )
+}
+
+pub(crate) fn command_read_head(span: &Span, header: &Header, size: usize) {
+    span.record("id", header.id)
+        .record("method", field::display(Method(&header.method)))
+        .record("status", header.status)
+        .record("data_type", header.data_type)
+        .record("size", size);
+}
+
+pub(crate) fn command_read(header: &Header, size: usize) {
+    debug!(
+        id = header.id,
+        method = %Method(&header.method),
+        status = header.status,
+        data_type = header.data_type,
+        size,
+        "command read"
+    );
+}
+
+pub(crate) fn command_body(data: &[u8]) {
+    tracing::trace!(data = ?data, "command body");
+}
+
+#[cfg(feature = "secure")]
+pub(crate) fn packet_read(size: usize, data_size: usize) {
+    tracing::trace!(size, data_size, "secure packet read");
+}
+
+#[cfg(feature = "secure")]
+pub(crate) fn packet_written(size: usize) {
+    tracing::trace!(size, "secure packet written");
+}
+
+#[cfg(feature = "secure")]
+pub(crate) fn packet_decrypt_failed(err: &dyn std::error::Error) {
+    tracing::warn!(error = %err, "secure packet decryption failed");
+}
+
+/// Span covering handshake of given side
+#[cfg(feature = "secure")]
+pub(crate) fn handshake_span(side: &'static str) -> Span {
+    debug_span!("secure_handshake", side)
+}
+
+#[cfg(feature = "secure")]
+pub(crate) fn handshake(header: &SecureHandshakeHeader, key_size: usize) {
+    debug!(
+        key_encrypt_type = header.key_encrypt_type,
+        encrypt_type = header.encrypt_type,
+        key_size,
+        "secure handshake"
+    );
+}
+
+#[cfg(feature = "secure")]
+pub(crate) fn handshake_failed(err: &dyn std::error::Error) {
+    tracing::warn!(error = %err, "secure handshake failed");
+}
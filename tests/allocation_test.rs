@@ -0,0 +1,131 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::{self, Cursor, Read, Write},
+};
+
+use loco_protocol::secure::{
+    codec::SecureCodec, crypto::CryptoStore, stream::SecureStream, SECURE_HEAD_SIZE,
+};
+
+/// Allocator counting allocations made on current thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+const PACKETS: usize = 1000;
+const DATA_SIZE: usize = 1024;
+
+#[test]
+pub fn codec_steady_state_allocation() {
+    let crypto = CryptoStore::new();
+    let data = vec![1_u8; DATA_SIZE];
+
+    let mut write_codec = SecureCodec::new(crypto.clone(), io::sink());
+    // Warm up write buffer and rng
+    write_codec
+        .write_data(&data)
+        .expect("Packet write must not fail");
+
+    let start = allocations();
+    for _ in 0..PACKETS {
+        write_codec
+            .write_data(&data)
+            .expect("Packet write must not fail");
+    }
+    assert_eq!(allocations() - start, 0);
+
+    let mut local = Vec::with_capacity((SECURE_HEAD_SIZE + DATA_SIZE) * (PACKETS + 1));
+    {
+        let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+        for _ in 0..=PACKETS {
+            codec.write_data(&data).expect("Packet write must not fail");
+        }
+    }
+
+    let mut read_codec = SecureCodec::new(crypto, Cursor::new(&local));
+    let mut buf = Vec::new();
+    read_codec
+        .read_packet_into(&mut buf)
+        .expect("Packet read must not fail");
+
+    let start = allocations();
+    for _ in 0..PACKETS {
+        read_codec
+            .read_packet_into(&mut buf)
+            .expect("Packet read must not fail");
+    }
+    assert_eq!(allocations() - start, 0);
+
+    assert_eq!(buf, data);
+}
+
+#[test]
+pub fn stream_steady_state_allocation() {
+    let crypto = CryptoStore::new();
+    let data = vec![2_u8; DATA_SIZE];
+
+    let mut write_stream = SecureStream::from_established(crypto.clone(), io::sink());
+    write_stream.write_all(&data).expect("Write must not fail");
+    write_stream.flush().expect("Flush must not fail");
+
+    let start = allocations();
+    for _ in 0..PACKETS {
+        write_stream.write_all(&data).expect("Write must not fail");
+        write_stream.flush().expect("Flush must not fail");
+    }
+    assert_eq!(allocations() - start, 0);
+
+    let mut local = Vec::with_capacity((SECURE_HEAD_SIZE + DATA_SIZE) * (PACKETS + 1));
+    {
+        let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+        for _ in 0..=PACKETS {
+            codec.write_data(&data).expect("Packet write must not fail");
+        }
+    }
+
+    let mut read_stream = SecureStream::from_established(crypto, Cursor::new(&local));
+    let mut buf = vec![0_u8; DATA_SIZE];
+    read_stream.read_exact(&mut buf).expect("Read must not fail");
+
+    let start = allocations();
+    for _ in 0..PACKETS {
+        read_stream.read_exact(&mut buf).expect("Read must not fail");
+    }
+    assert_eq!(allocations() - start, 0);
+
+    assert_eq!(buf, data);
+}
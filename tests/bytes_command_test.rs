@@ -0,0 +1,96 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use std::io::Cursor;
+
+use bytes::Bytes;
+use common::test_command;
+use loco_protocol::{
+    command::{
+        codec::{decode::decode_command_bytes, encode::encode_frame, CommandCodec},
+        CommandParseError, HEADER_SIZE, HEAD_SIZE,
+    },
+    secure::{codec::SecureCodec, crypto::CryptoStore, stream::SecureStream},
+};
+
+#[test]
+pub fn bytes_command_clone_shares_data() {
+    let command = test_command(1, "MSG", vec![1, 2, 3, 4]).into_bytes();
+    let cloned = command.clone();
+
+    assert_eq!(command, cloned);
+    assert_eq!(command.data.as_ptr(), cloned.data.as_ptr());
+}
+
+#[test]
+pub fn bytes_command_decode_from_packet() {
+    let crypto = CryptoStore::new();
+    let command1 = test_command(1, "TEST1", vec![1; 16]);
+    let command2 = test_command(1, "TEST2", vec![2; 8]);
+
+    let mut stream = SecureStream::from_established(crypto.clone(), Vec::<u8>::new());
+    {
+        let mut codec = CommandCodec::new(&mut stream);
+        codec.write(&command1).expect("Command write must not fail");
+    }
+    let (_, local) = stream.into_inner();
+
+    let mut secure_codec = SecureCodec::new(crypto, Cursor::new(local));
+    let packet = secure_codec
+        .read_packet()
+        .expect("Packet read must not fail")
+        .expect("Stream must not end");
+
+    let mut buf = Bytes::from(packet.data);
+    let start = buf.as_ptr();
+
+    let decoded = decode_command_bytes(&mut buf).expect("Packet must contain whole command");
+
+    assert_eq!(decoded.header, command1.header);
+    assert_eq!(decoded.data, command1.data);
+    assert!(buf.is_empty());
+
+    // Data is sliced out of packet buffer without copy
+    assert_eq!(decoded.data.as_ptr() as usize - start as usize, HEAD_SIZE);
+
+    let mut partial = Bytes::from(command2.data.clone());
+    assert!(matches!(
+        decode_command_bytes(&mut partial),
+        Err(CommandParseError::Incomplete { .. })
+    ));
+    assert_eq!(partial.len(), 8);
+}
+
+#[test]
+pub fn bytes_command_write() {
+    let mut local = Vec::<u8>::new();
+    let command = test_command(1, "TEST", vec![8; 4]);
+
+    let mut write_codec = CommandCodec::new(&mut local);
+    write_codec
+        .write(&command.clone().into_bytes())
+        .expect("Command write must not fail");
+
+    let mut read_codec = CommandCodec::new(Cursor::new(&mut local));
+    let (_, read) = read_codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+
+    assert_eq!(read, command);
+}
+
+#[test]
+pub fn bytes_command_max_data_size() {
+    let mut buf = encode_frame(&test_command(1, "MSG", Vec::new()));
+    buf[HEADER_SIZE..HEAD_SIZE].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut buf = Bytes::from(buf);
+    assert!(decode_command_bytes(&mut buf).is_err());
+    assert_eq!(buf.len(), HEAD_SIZE);
+}
@@ -4,9 +4,15 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
+mod common;
+
 use std::io::Cursor;
 
-use loco_protocol::command::{codec::CommandCodec, Command, Header};
+use common::{test_commands, ChunkedReader};
+use loco_protocol::{
+    command::{codec::CommandCodec, Command, Header},
+    secure::{codec::SecureCodec, crypto::CryptoStore, stream::SecureStream},
+};
 
 #[test]
 pub fn codec_read_write() {
@@ -17,7 +23,7 @@ pub fn codec_read_write() {
             id: 0,
             data_type: 0,
             status: 0,
-            method: Header::to_method(&"TEST1"),
+            method: Header::to_method("TEST1"),
         },
         data: vec![0_u8; 4],
     };
@@ -27,7 +33,7 @@ pub fn codec_read_write() {
             id: 0,
             data_type: 0,
             status: 0,
-            method: Header::to_method(&"TEST2"),
+            method: Header::to_method("TEST2"),
         },
         data: vec![8_u8; 4],
     };
@@ -44,9 +50,81 @@ pub fn codec_read_write() {
 
     let mut read_codec = CommandCodec::new(Cursor::new(&mut local));
 
-    let (_, command1) = read_codec.read().expect("Command read must not fail");
+    let (_, command1) = read_codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
     assert_eq!(command1, test_command1);
 
-    let (_, command2) = read_codec.read().expect("Command read must not fail");
+    let (_, command2) = read_codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
     assert_eq!(command2, test_command2);
 }
+
+#[test]
+pub fn codec_secure_single_packet() {
+    let mut local = Vec::<u8>::new();
+
+    let crypto = CryptoStore::new();
+
+    let test_command = Command {
+        header: Header {
+            id: 1,
+            data_type: 0,
+            status: 0,
+            method: Header::to_method("TEST"),
+        },
+        data: vec![8_u8; 32],
+    };
+
+    let mut write_codec = CommandCodec::new(SecureStream::from_established(crypto.clone(), &mut local));
+    write_codec
+        .write(&test_command)
+        .expect("Command write must not fail");
+    drop(write_codec);
+
+    let mut secure_codec = SecureCodec::new(crypto, Cursor::new(local));
+    let packet = secure_codec
+        .read_packet()
+        .expect("Data reading must not fail")
+        .expect("Stream must not end");
+
+    assert_eq!(packet.data.len(), 22 + 32);
+    assert!(secure_codec
+        .read_packet()
+        .expect("Data reading must not fail")
+        .is_none());
+}
+
+#[test]
+pub fn codec_read_async_cancel() {
+    use futures::FutureExt;
+
+    let mut local = Vec::<u8>::new();
+
+    let test_commands = test_commands();
+
+    let mut write_codec = CommandCodec::new(&mut local);
+    for command in &test_commands {
+        write_codec
+            .write(command)
+            .expect("Command write must not fail");
+    }
+
+    let mut read_codec = CommandCodec::new(ChunkedReader::new(futures::io::Cursor::new(local), 5));
+
+    for test_command in &test_commands {
+        // Poll once and drop future, like a lost select! branch
+        let (_, command) = loop {
+            if let Some(res) = read_codec.read_async().now_or_never() {
+                break res
+                    .expect("Command read must not fail")
+                    .expect("Stream must not end");
+            }
+        };
+
+        assert_eq!(&command, test_command);
+    }
+}
@@ -0,0 +1,50 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use common::test_command;
+use loco_protocol::command::codec::{decoder::CommandDecoder, encode::encode_frame};
+
+#[test]
+pub fn command_decoder_chunked() {
+    let test_commands = (0..3)
+        .map(|i| test_command(i, "TEST", vec![i as u8; 30]))
+        .collect::<Vec<_>>();
+
+    let stream = test_commands
+        .iter()
+        .flat_map(encode_frame)
+        .collect::<Vec<u8>>();
+
+    let mut decoder = CommandDecoder::new();
+    let mut commands = Vec::new();
+
+    for chunk in stream.chunks(7) {
+        decoder.feed(chunk);
+        commands.extend(&mut decoder);
+    }
+
+    assert_eq!(commands, test_commands);
+    assert!(decoder.is_empty());
+}
+
+#[test]
+pub fn command_decoder_incomplete() {
+    let frame = encode_frame(&test_command(1, "TEST", vec![1, 2, 3, 4]));
+
+    let mut decoder = CommandDecoder::new();
+    assert_eq!(decoder.needed(), 22);
+
+    decoder.feed(&frame[..24]);
+    assert_eq!(decoder.needed(), 2);
+    assert!(decoder.decode().is_none());
+    assert_eq!(decoder.buffered(), &frame[..24]);
+
+    decoder.feed(&frame[24..]);
+    let command = decoder.decode().expect("Command must be decoded");
+    assert_eq!(command.data, vec![1, 2, 3, 4]);
+}
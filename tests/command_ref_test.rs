@@ -0,0 +1,87 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use common::test_command;
+use loco_protocol::command::{
+    codec::encode::encode_frame, CommandParseError, CommandRef, HEADER_SIZE, HEAD_SIZE,
+};
+
+#[test]
+pub fn command_ref_parse() {
+    let mut command1 = test_command(1, "MSG", vec![1, 2, 3]);
+    command1.header.status = -1;
+    command1.header.data_type = 2;
+    let command2 = test_command(2, "LOGINLIST", Vec::new());
+
+    let buf = [encode_frame(&command1), encode_frame(&command2)].concat();
+
+    let (parsed, consumed) = CommandRef::parse(&buf).expect("Command parse must not fail");
+    assert_eq!(consumed, HEAD_SIZE + 3);
+    assert_eq!(parsed.header.id(), 1);
+    assert_eq!(parsed.header.status(), -1);
+    assert_eq!(parsed.header.method(), Ok("MSG"));
+    assert_eq!(parsed.header.data_type(), 2);
+    assert_eq!(parsed.data, &[1, 2, 3]);
+
+    // Data is borrowed from buf
+    assert_eq!(parsed.data.as_ptr(), buf[HEAD_SIZE..].as_ptr());
+    assert_eq!(parsed.to_owned(), command1);
+
+    let (parsed, consumed) =
+        CommandRef::parse(&buf[consumed..]).expect("Command parse must not fail");
+    assert_eq!(consumed, HEAD_SIZE);
+    assert_eq!(parsed.header.method(), Ok("LOGINLIST"));
+    assert_eq!(parsed.header.to_header(), command2.header);
+    assert!(parsed.data.is_empty());
+}
+
+#[test]
+pub fn command_ref_incomplete() {
+    let buf = encode_frame(&test_command(1, "MSG", vec![0; 10]));
+
+    assert_eq!(
+        CommandRef::parse(&buf[..10]),
+        Err(CommandParseError::Incomplete {
+            expected: HEAD_SIZE,
+            got: 10
+        })
+    );
+
+    assert_eq!(
+        CommandRef::parse(&buf[..HEAD_SIZE + 5]),
+        Err(CommandParseError::Incomplete {
+            expected: HEAD_SIZE + 10,
+            got: HEAD_SIZE + 5
+        })
+    );
+}
+
+#[test]
+pub fn command_ref_max_data_size() {
+    let mut buf = encode_frame(&test_command(1, "MSG", Vec::new()));
+    buf[HEADER_SIZE..HEAD_SIZE].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let res = CommandRef::parse(&buf);
+
+    // Whole size overflows usize only on 32 bit targets
+    match HEAD_SIZE.checked_add(u32::MAX as usize) {
+        Some(expected) => assert_eq!(
+            res,
+            Err(CommandParseError::Incomplete {
+                expected,
+                got: HEAD_SIZE
+            })
+        ),
+        None => assert_eq!(
+            res,
+            Err(CommandParseError::TooLarge {
+                data_size: u32::MAX
+            })
+        ),
+    }
+}
@@ -0,0 +1,156 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+//! Fixtures shared between integration tests
+
+#![allow(dead_code)]
+
+use std::{
+    io::{self, Cursor, Read, Write},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{AsyncRead, AsyncWrite};
+use loco_protocol::command::{codec::CommandCodec, Command, Header};
+
+pub fn test_command(id: i32, method: &str, data: Vec<u8>) -> Command {
+    Command {
+        header: Header {
+            id,
+            data_type: 0,
+            status: 0,
+            method: Header::to_method(method),
+        },
+        data,
+    }
+}
+
+/// Three TEST commands with 40 bytes of data each
+pub fn test_commands() -> Vec<Command> {
+    (0..3)
+        .map(|i| test_command(i, "TEST", vec![i as u8; 40]))
+        .collect()
+}
+
+/// Encode commands into one buffer
+pub fn encode(commands: &[Command]) -> Vec<u8> {
+    let mut local = Vec::new();
+    let mut codec = CommandCodec::new(&mut local);
+
+    for command in commands {
+        codec.write(command).expect("Command write must not fail");
+    }
+
+    local
+}
+
+/// Stream reading prepared input and collecting output
+pub struct Loopback {
+    pub input: Cursor<Vec<u8>>,
+    pub output: Vec<u8>,
+}
+
+impl Loopback {
+    pub fn new(input: Vec<u8>) -> Self {
+        Self {
+            input: Cursor::new(input),
+            output: Vec::new(),
+        }
+    }
+}
+
+impl Read for Loopback {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for Loopback {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reader returning [Poll::Pending] on every other poll and at most `chunk_size` bytes at once
+pub struct ChunkedReader<R> {
+    inner: R,
+    chunk_size: usize,
+    pending: bool,
+}
+
+impl<R> ChunkedReader<R> {
+    pub fn new(inner: R, chunk_size: usize) -> Self {
+        Self {
+            inner,
+            chunk_size,
+            pending: false,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ChunkedReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.pending = !self.pending;
+
+        if self.pending {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        let len = buf.len().min(self.chunk_size);
+        Pin::new(&mut self.inner).poll_read(cx, &mut buf[..len])
+    }
+}
+
+/// Writer accepting at most 3 bytes per poll and returning [Poll::Pending] in between
+#[derive(Default)]
+pub struct TrickleWriter {
+    pub inner: Vec<u8>,
+    pending: bool,
+}
+
+impl TrickleWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AsyncWrite for TrickleWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.pending = !self.pending;
+
+        if self.pending {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        let len = buf.len().min(3);
+        self.inner.extend_from_slice(&buf[..len]);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
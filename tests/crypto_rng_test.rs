@@ -0,0 +1,62 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use loco_protocol::secure::{
+    codec::encode::to_encrypted_packet, crypto::CryptoStore, session::client::to_handshake_packet,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+pub fn seeded_encrypted_packet() {
+    let crypto = CryptoStore::new_seeded(0);
+
+    let packet = to_encrypted_packet(&crypto, &[1, 2, 3, 4]).expect("Packet encryption failed");
+
+    assert_eq!(
+        packet,
+        from_hex("14000000c00f8401696a5bdc34f5a6d2ff3f922f644c9b11")
+    );
+}
+
+#[test]
+pub fn seeded_handshake_packet() {
+    let private_key = RsaPrivateKey::new(&mut ChaCha20Rng::seed_from_u64(1), 512)
+        .expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let crypto = CryptoStore::new_seeded(0);
+
+    let packet = to_handshake_packet(&crypto, &public_key).expect("Handshake packet failed");
+
+    assert_eq!(
+        packet,
+        from_hex(concat!(
+            "400000000c00000002000000",
+            "94386c747e0769a50ebb0b7c76e230ee362dede12e1723142f847d9326083bc0",
+            "82c57815eef321139291c3a2b5f2396b651820ea46bf8d2c47491b70d18b6383"
+        ))
+    );
+}
+
+#[test]
+pub fn injected_rng() {
+    let crypto1 = CryptoStore::with_rng(ChaCha20Rng::seed_from_u64(0));
+    let crypto2 = CryptoStore::new_seeded(0);
+
+    assert_eq!(
+        to_encrypted_packet(&crypto1, &[1, 2, 3, 4]).unwrap(),
+        to_encrypted_packet(&crypto2, &[1, 2, 3, 4]).unwrap()
+    );
+}
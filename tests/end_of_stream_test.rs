@@ -0,0 +1,202 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::io::Cursor;
+
+use futures::executor::block_on;
+use loco_protocol::{
+    command::{
+        codec::{CommandCodec, StreamError},
+        Command, Header, HEAD_SIZE,
+    },
+    secure::{
+        codec::{SecureCodec, SecureError},
+        crypto::CryptoStore,
+        session::{
+            server::{read_handshake, read_handshake_async},
+            SecureHandshakeError,
+        },
+        stream::SecureStream,
+        SECURE_HANDSHAKE_HEAD_SIZE, SECURE_HEAD_SIZE,
+    },
+};
+
+fn test_frame() -> (Command, Vec<u8>) {
+    let command = Command {
+        header: Header {
+            id: 1,
+            status: 0,
+            method: Header::to_method("TEST"),
+            data_type: 0,
+        },
+        data: vec![1_u8; 8],
+    };
+
+    let mut frame = Vec::new();
+    CommandCodec::new(&mut frame)
+        .write(&command)
+        .expect("Command write must not fail");
+
+    (command, frame)
+}
+
+#[test]
+pub fn command_codec_end_of_stream() {
+    let (command, frame) = test_frame();
+
+    let mut codec = CommandCodec::new(Cursor::new(&frame));
+    let (_, read) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(read, command);
+    assert!(codec.read().expect("Clean end must not fail").is_none());
+
+    let mut codec = CommandCodec::new(Cursor::new(&frame[..10]));
+    assert!(matches!(
+        codec.read(),
+        Err(StreamError::TruncatedFrame {
+            expected: HEAD_SIZE,
+            got: 10
+        })
+    ));
+
+    let mut codec = CommandCodec::new(Cursor::new(&frame[..HEAD_SIZE + 3]));
+    assert!(matches!(
+        codec.read(),
+        Err(StreamError::TruncatedFrame {
+            expected: 30,
+            got: 25
+        })
+    ));
+}
+
+#[test]
+pub fn command_codec_end_of_stream_async() {
+    let (command, frame) = test_frame();
+
+    block_on(async {
+        let mut codec = CommandCodec::new(futures::io::Cursor::new(&frame));
+        let (_, read) = codec
+            .read_async()
+            .await
+            .expect("Command read must not fail")
+            .expect("Stream must not end");
+        assert_eq!(read, command);
+        assert!(codec
+            .read_async()
+            .await
+            .expect("Clean end must not fail")
+            .is_none());
+
+        let mut codec = CommandCodec::new(futures::io::Cursor::new(&frame[..HEAD_SIZE + 3]));
+        assert!(matches!(
+            codec.read_async().await,
+            Err(StreamError::TruncatedFrame {
+                expected: 30,
+                got: 25
+            })
+        ));
+    });
+}
+
+#[test]
+pub fn secure_codec_end_of_stream() {
+    let crypto = CryptoStore::new();
+
+    let mut local = Vec::new();
+    SecureCodec::new(crypto.clone(), &mut local)
+        .write_data(&[1, 2, 3, 4])
+        .expect("Packet write must not fail");
+
+    let mut codec = SecureCodec::new(crypto.clone(), Cursor::new(&local));
+    codec
+        .read_packet()
+        .expect("Packet read must not fail")
+        .expect("Stream must not end");
+    assert!(codec
+        .read_packet()
+        .expect("Clean end must not fail")
+        .is_none());
+
+    let mut codec = SecureCodec::new(crypto.clone(), Cursor::new(&local[..SECURE_HEAD_SIZE + 1]));
+    assert!(matches!(
+        codec.read_packet(),
+        Err(SecureError::TruncatedFrame {
+            expected: 24,
+            got: 21
+        })
+    ));
+
+    let mut codec = SecureCodec::new(crypto, futures::io::Cursor::new(&local[..5]));
+    assert!(matches!(
+        block_on(codec.read_packet_async()),
+        Err(SecureError::TruncatedFrame {
+            expected: SECURE_HEAD_SIZE,
+            got: 5
+        })
+    ));
+}
+
+#[test]
+pub fn secure_stream_end_of_stream() {
+    let crypto = CryptoStore::new();
+    let (command, frame) = test_frame();
+
+    let mut local = Vec::new();
+    let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+    codec.write_data(&frame).expect("Packet write must not fail");
+
+    let mut codec = CommandCodec::new(SecureStream::from_established(crypto.clone(), Cursor::new(&local)));
+    let (_, read) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(read, command);
+    assert!(codec.read().expect("Clean end must not fail").is_none());
+
+    // Packet boundary in middle of command
+    let mut local = Vec::new();
+    let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+    codec
+        .write_data(&frame[..HEAD_SIZE])
+        .expect("Packet write must not fail");
+
+    let mut codec = CommandCodec::new(SecureStream::from_established(crypto, Cursor::new(&local)));
+    assert!(matches!(
+        codec.read(),
+        Err(StreamError::TruncatedFrame {
+            expected: 30,
+            got: 22
+        })
+    ));
+}
+
+#[test]
+pub fn handshake_end_of_stream() {
+    assert!(read_handshake(&mut Cursor::new(&[]))
+        .expect("Clean end must not fail")
+        .is_none());
+
+    assert!(matches!(
+        read_handshake(&mut Cursor::new(&[0_u8; 5])),
+        Err(SecureHandshakeError::TruncatedFrame {
+            expected: SECURE_HANDSHAKE_HEAD_SIZE,
+            got: 5
+        })
+    ));
+
+    let mut head = vec![0_u8; SECURE_HANDSHAKE_HEAD_SIZE];
+    head[0] = 16;
+    head.extend_from_slice(&[0_u8; 4]);
+    assert!(matches!(
+        block_on(read_handshake_async(&mut futures::io::Cursor::new(&head))),
+        Err(SecureHandshakeError::TruncatedFrame {
+            expected: 28,
+            got: 16
+        })
+    ));
+}
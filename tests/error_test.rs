@@ -0,0 +1,159 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    error::Error,
+    io::{self, Cursor, Read},
+};
+
+use loco_protocol::{
+    command::codec::{CommandCodec, StreamError},
+    error::{ErrorKind, LocoError},
+    secure::{
+        codec::SecureError,
+        crypto::{CryptoError, CryptoStore},
+        session::{SecureHandshakeError, SecureServerSession},
+        stream::SecureStream,
+    },
+};
+use rand::rngs::OsRng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+/// Secure packet with data size smaller than header
+fn corrupted_packet() -> Vec<u8> {
+    let mut packet = 4_u32.to_le_bytes().to_vec();
+    packet.extend_from_slice(&[0; 16]);
+
+    packet
+}
+
+#[test]
+pub fn error_secure_stream_keeps_cause() {
+    let mut stream = SecureStream::from_established(CryptoStore::new(), Cursor::new(corrupted_packet()));
+
+    let err = stream
+        .read(&mut [0; 8])
+        .expect_err("Corrupted packet must fail");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let inner = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<SecureError>())
+        .expect("Cause must be kept");
+    assert!(matches!(inner, SecureError::Crypto(CryptoError::CorruptedData)));
+
+    let err = LocoError::from(err);
+    assert_eq!(err.kind(), ErrorKind::Crypto);
+    assert!(err.is_fatal());
+    assert_eq!(err.to_string(), "Crypto error");
+
+    let source = err.source().expect("Source must be kept");
+    assert!(source.is::<SecureError>());
+    assert!(source
+        .source()
+        .expect("Cause must be kept")
+        .is::<CryptoError>());
+}
+
+#[test]
+pub fn error_command_over_secure_stream() {
+    let mut codec = CommandCodec::new(SecureStream::from_established(
+        CryptoStore::new(),
+        Cursor::new(corrupted_packet()),
+    ));
+
+    let err = codec.read().expect_err("Corrupted packet must fail");
+    assert!(matches!(err, StreamError::Io(_)));
+
+    let err = LocoError::from(err);
+    assert_eq!(err.kind(), ErrorKind::Crypto);
+    assert!(!err.is_retryable());
+}
+
+#[test]
+pub fn error_truncated_frame_retryable() {
+    let mut codec = CommandCodec::new(Cursor::new(vec![0_u8; 10]));
+
+    let err = codec.read().expect_err("Truncated command must fail");
+    assert_eq!(err.kind(), ErrorKind::Framing);
+    assert!(err.is_retryable());
+
+    let io_err = io::Error::from(err);
+    assert_eq!(io_err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let err = LocoError::from(io_err);
+    assert_eq!(err.kind(), ErrorKind::Framing);
+    assert!(err.is_retryable());
+}
+
+#[test]
+pub fn error_io_classification() {
+    let err = LocoError::from(io::Error::from(io::ErrorKind::ConnectionReset));
+    assert_eq!(err.kind(), ErrorKind::Io);
+    assert!(err.is_retryable());
+
+    let err = LocoError::from(io::Error::from(io::ErrorKind::PermissionDenied));
+    assert_eq!(err.kind(), ErrorKind::Io);
+    assert!(err.is_fatal());
+}
+
+#[test]
+pub fn error_handshake_classification() {
+    let err = SecureHandshakeError::InvalidKey;
+    assert_eq!(err.kind(), ErrorKind::Handshake);
+    assert!(err.is_fatal());
+
+    let err = LocoError::from(SecureHandshakeError::Canceled);
+    assert_eq!(err.kind(), ErrorKind::Handshake);
+    assert!(err.is_retryable());
+
+    let err = LocoError::from(SecureHandshakeError::Io(io::Error::from(
+        io::ErrorKind::TimedOut,
+    )));
+    assert_eq!(err.kind(), ErrorKind::Io);
+    assert!(err.is_retryable());
+    assert!(err.source().is_some());
+}
+
+#[test]
+pub fn error_handshake_entry_points() {
+    let private_key = RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let err = SecureStream::accept(Cursor::new(vec![0_u8; 5]), &private_key)
+        .expect_err("Truncated handshake must fail");
+    assert_eq!(err.kind(), ErrorKind::Framing);
+    assert!(err.is_retryable());
+
+    let err = SecureServerSession::new(private_key)
+        .handshake(Cursor::new(Vec::new()))
+        .expect_err("Missing handshake must fail");
+    assert_eq!(err.kind(), ErrorKind::Io);
+    assert!(err.is_retryable());
+
+    let err = SecureStream::connect(&mut [0_u8; 0][..], &public_key)
+        .expect_err("Full stream must fail");
+    assert_eq!(err.kind(), ErrorKind::Io);
+    assert!(err.is_fatal());
+}
+
+#[test]
+pub fn error_io_display_includes_cause() {
+    let io_err = || io::Error::new(io::ErrorKind::ConnectionReset, "peer reset");
+
+    assert_eq!(
+        StreamError::from(io_err()).to_string(),
+        "Command stream IO error: peer reset"
+    );
+    assert_eq!(
+        SecureError::from(io_err()).to_string(),
+        "Secure stream IO error: peer reset"
+    );
+    assert_eq!(
+        SecureHandshakeError::from(io_err()).to_string(),
+        "Handshake IO error: peer reset"
+    );
+}
@@ -4,8 +4,9 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
-use std::io::Cursor;
+use std::io::{Cursor, Read, Write};
 
+use futures::executor::block_on;
 use loco_protocol::secure::{crypto::CryptoStore, session::{SecureClientSession, SecureServerSession}, stream::SecureStream};
 use rand::rngs::OsRng;
 use rsa::{RsaPrivateKey, RsaPublicKey};
@@ -16,13 +17,65 @@ pub fn handshake() {
     let public_key = RsaPublicKey::from(&private_key);
 
     let mut local = Vec::<u8>::new();
-    let mut stream = SecureStream::new(CryptoStore::new(), &mut local);
 
     let client_session = SecureClientSession::new(public_key);
 
-    client_session.handshake(&mut stream).expect("Client handshake failed");
+    let mut client = client_session.handshake(CryptoStore::new(), &mut local).expect("Client handshake failed");
+    client.write_all(&[1, 2, 3, 4]).expect("Data writing must not fail");
+    client.flush().expect("Data flushing must not fail");
 
-    let mut server_session = SecureServerSession::new(private_key);
+    let server_session = SecureServerSession::new(private_key);
 
-    server_session.handshake(&mut Cursor::new(&mut local)).expect("Server handshake failed");
+    let mut server = server_session.handshake(Cursor::new(&mut local)).expect("Server handshake failed");
+
+    let mut data = [0_u8; 4];
+    server.read_exact(&mut data).expect("Data reading must not fail");
+
+    assert_eq!(data, [1, 2, 3, 4]);
+}
+
+#[test]
+pub fn connect_accept() {
+    let private_key = RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let mut local = Vec::<u8>::new();
+
+    let mut client = SecureStream::connect(&mut local, &public_key).expect("Client handshake failed");
+    client.write_all(&[1, 2, 3, 4]).expect("Data writing must not fail");
+    client.flush().expect("Data flushing must not fail");
+
+    let mut server = SecureStream::accept(Cursor::new(&mut local), &private_key).expect("Server handshake failed");
+
+    let mut data = [0_u8; 4];
+    server.read_exact(&mut data).expect("Data reading must not fail");
+
+    assert_eq!(data, [1, 2, 3, 4]);
+}
+
+#[test]
+pub fn connect_accept_async() {
+    let private_key = RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    block_on(async {
+        use futures::{AsyncReadExt, AsyncWriteExt};
+
+        let mut local = Vec::<u8>::new();
+
+        let mut client = SecureStream::connect_async(futures::io::Cursor::new(&mut local), &public_key)
+            .await
+            .expect("Client handshake failed");
+        client.write_all(&[1, 2, 3, 4]).await.expect("Data writing must not fail");
+        client.flush().await.expect("Data flushing must not fail");
+
+        let mut server = SecureStream::accept_async(futures::io::Cursor::new(&mut local), &private_key)
+            .await
+            .expect("Server handshake failed");
+
+        let mut data = [0_u8; 4];
+        server.read_exact(&mut data).await.expect("Data reading must not fail");
+
+        assert_eq!(data, [1, 2, 3, 4]);
+    });
 }
@@ -0,0 +1,196 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use std::io::Cursor;
+
+use common::{encode, test_command, ChunkedReader};
+use futures::{executor::block_on, FutureExt};
+use loco_protocol::command::{codec::CommandCodec, Command};
+
+fn test_commands() -> Vec<Command> {
+    ["SKIP", "BUF", "COPY"]
+        .iter()
+        .enumerate()
+        .map(|(i, method)| test_command(i as i32, method, vec![i as u8; 20000 + i]))
+        .collect()
+}
+
+#[test]
+pub fn header_first_read() {
+    let commands = test_commands();
+    let mut codec = CommandCodec::new(Cursor::new(encode(&commands)));
+
+    let (header, data_size) = codec
+        .read_head()
+        .expect("Head read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(header, commands[0].header);
+    assert_eq!(data_size, commands[0].data.len());
+    codec.skip_body().expect("Body skip must not fail");
+
+    let (header, data_size) = codec
+        .read_head()
+        .expect("Head read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(header, commands[1].header);
+
+    let mut buf = vec![0_u8; 32768];
+    let read = codec.read_body(&mut buf).expect("Body read must not fail");
+    assert_eq!(read, data_size);
+    assert_eq!(&buf[..read], &commands[1].data[..]);
+
+    codec
+        .read_head()
+        .expect("Head read must not fail")
+        .expect("Stream must not end");
+    let mut copied = Vec::new();
+    let written = codec
+        .copy_body(&mut copied)
+        .expect("Body copy must not fail");
+    assert_eq!(written as usize, copied.len());
+    assert_eq!(copied, commands[2].data);
+
+    assert!(codec.read_head().expect("Head read must not fail").is_none());
+}
+
+#[test]
+pub fn header_first_unread_body_skipped() {
+    let commands = test_commands();
+    let mut codec = CommandCodec::new(Cursor::new(encode(&commands)));
+
+    codec.read_head().expect("Head read must not fail");
+    codec.read_head().expect("Head read must not fail");
+
+    let (_, command) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(command, commands[2]);
+}
+
+#[test]
+pub fn header_first_read_async() {
+    let commands = test_commands();
+    let mut codec = CommandCodec::new(futures::io::Cursor::new(encode(&commands)));
+
+    block_on(async {
+        let (header, _) = codec
+            .read_head_async()
+            .await
+            .expect("Head read must not fail")
+            .expect("Stream must not end");
+        assert_eq!(header, commands[0].header);
+        codec
+            .skip_body_async()
+            .await
+            .expect("Body skip must not fail");
+
+        let (_, data_size) = codec
+            .read_head_async()
+            .await
+            .expect("Head read must not fail")
+            .expect("Stream must not end");
+        let mut buf = vec![0_u8; data_size];
+        codec
+            .read_body_async(&mut buf)
+            .await
+            .expect("Body read must not fail");
+        assert_eq!(buf, commands[1].data);
+
+        codec
+            .read_head_async()
+            .await
+            .expect("Head read must not fail")
+            .expect("Stream must not end");
+        let mut copied = Vec::new();
+        codec
+            .copy_body_async(&mut copied)
+            .await
+            .expect("Body copy must not fail");
+        assert_eq!(copied, commands[2].data);
+
+        assert!(codec
+            .read_head_async()
+            .await
+            .expect("Head read must not fail")
+            .is_none());
+    });
+}
+
+#[test]
+pub fn header_first_read_async_cancel() {
+    let commands = test_commands();
+    let mut codec = CommandCodec::new(ChunkedReader::new(
+        futures::io::Cursor::new(encode(&commands)),
+        5000,
+    ));
+
+    for command in &commands {
+        // Poll once and drop future, like a lost select! branch
+        let (header, data_size) = loop {
+            if let Some(res) = codec.read_head_async().now_or_never() {
+                break res
+                    .expect("Head read must not fail")
+                    .expect("Stream must not end");
+            }
+        };
+        assert_eq!(header, command.header);
+
+        let mut buf = vec![0_u8; data_size];
+        loop {
+            if let Some(res) = codec.read_body_async(&mut buf).now_or_never() {
+                res.expect("Body read must not fail");
+                break;
+            }
+        }
+        assert_eq!(buf, command.data);
+    }
+}
+
+#[test]
+pub fn header_first_small_body_buffer() {
+    let commands = test_commands();
+    let mut codec = CommandCodec::new(Cursor::new(encode(&commands)));
+
+    codec
+        .read_head()
+        .expect("Head read must not fail")
+        .expect("Stream must not end");
+
+    let mut buf = vec![0_u8; 100];
+    let err = codec
+        .read_body(&mut buf)
+        .expect_err("Small buffer must be rejected");
+    assert_eq!(
+        std::io::Error::from(err).kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    assert_eq!(codec.body_remaining(), commands[0].data.len());
+
+    let mut body = vec![0_u8; commands[0].data.len()];
+    codec.read_body(&mut body).expect("Body read must not fail");
+    assert_eq!(body, commands[0].data);
+
+    let mut codec = CommandCodec::new(futures::io::Cursor::new(encode(&commands)));
+    block_on(async {
+        codec
+            .read_head_async()
+            .await
+            .expect("Head read must not fail")
+            .expect("Stream must not end");
+
+        let err = codec
+            .read_body_async(&mut buf)
+            .await
+            .expect_err("Small buffer must be rejected");
+        assert_eq!(
+            std::io::Error::from(err).kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    });
+}
@@ -0,0 +1,172 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use std::io::Cursor;
+
+use common::{encode, test_command, Loopback};
+use loco_protocol::command::{
+    codec::{
+        intercept::{Action, InterceptedCodec, Interceptor, InterceptorChain},
+        CommandCodec,
+    },
+    Command,
+};
+
+fn intercepted(input: &[Command], chain: InterceptorChain) -> InterceptedCodec<Loopback> {
+    InterceptedCodec::new(CommandCodec::new(Loopback::new(encode(input))), chain)
+}
+
+fn written(codec: InterceptedCodec<Loopback>) -> Vec<Command> {
+    let (codec, _) = codec.into_inner();
+
+    let mut codec = CommandCodec::new(Cursor::new(codec.into_inner().output));
+    let mut commands = Vec::new();
+    while let Some((_, command)) = codec.read().expect("Command read must not fail") {
+        commands.push(command);
+    }
+
+    commands
+}
+
+/// Appends tag to data of every command
+struct Tag(u8);
+
+impl Interceptor for Tag {
+    fn inbound(&mut self, mut command: Command) -> Action {
+        command.data.push(self.0);
+        Action::Pass(command)
+    }
+
+    fn outbound(&mut self, mut command: Command) -> Action {
+        command.data.push(self.0);
+        Action::Pass(command)
+    }
+}
+
+/// Answers PING locally and drops inbound KICKOUT
+struct PingResponder;
+
+impl Interceptor for PingResponder {
+    fn inbound(&mut self, command: Command) -> Action {
+        match command.header.method().as_deref() {
+            Ok("PING") => Action::Respond(test_command(command.header.id, "PING", vec![])),
+            Ok("KICKOUT") => Action::Drop,
+            _ => Action::Pass(command),
+        }
+    }
+
+    fn outbound(&mut self, command: Command) -> Action {
+        match command.header.method().as_deref() {
+            Ok("GETCONF") => Action::Respond(test_command(command.header.id, "GETCONF", vec![9])),
+            _ => Action::Pass(command),
+        }
+    }
+}
+
+#[test]
+pub fn interceptor_chain_order() {
+    let mut codec = intercepted(
+        &[test_command(1, "MSG", vec![])],
+        InterceptorChain::new().with(Tag(1)).with(Tag(2)),
+    );
+
+    codec
+        .write(test_command(0, "WRITE", vec![]))
+        .expect("Command write must not fail");
+
+    let command = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(command.data, vec![2, 1]);
+
+    assert_eq!(written(codec)[0].data, vec![1, 2]);
+}
+
+#[test]
+pub fn interceptor_drop_and_respond_inbound() {
+    let mut codec = intercepted(
+        &[
+            test_command(1, "PING", vec![]),
+            test_command(2, "KICKOUT", vec![]),
+            test_command(3, "MSG", vec![3]),
+        ],
+        InterceptorChain::new().with(PingResponder),
+    );
+
+    let command = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(command, test_command(3, "MSG", vec![3]));
+    assert!(codec.read().expect("Command read must not fail").is_none());
+
+    assert_eq!(written(codec), vec![test_command(1, "PING", vec![])]);
+}
+
+#[test]
+pub fn interceptor_respond_outbound() {
+    let mut codec = intercepted(
+        &[test_command(2, "MSG", vec![])],
+        InterceptorChain::new().with(Tag(1)).with(PingResponder),
+    );
+
+    let size = codec
+        .write(test_command(1, "GETCONF", vec![]))
+        .expect("Command write must not fail");
+    assert_eq!(size, 0);
+
+    // Response is returned first and passes interceptors before responder
+    let response = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(response, test_command(1, "GETCONF", vec![9, 1]));
+
+    let command = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(command.header.id, 2);
+
+    assert!(written(codec).is_empty());
+}
+
+#[test]
+pub fn interceptor_respond_inbound_passes_rest_of_chain() {
+    let mut codec = intercepted(
+        &[test_command(1, "PING", vec![])],
+        InterceptorChain::new()
+            .with(Tag(1))
+            .with(PingResponder)
+            .with(Tag(2)),
+    );
+
+    assert!(codec.read().expect("Command read must not fail").is_none());
+
+    // Response skips interceptors closer to application
+    assert_eq!(written(codec), vec![test_command(1, "PING", vec![2])]);
+}
+
+#[test]
+pub fn interceptor_async() {
+    let mut local = Vec::new();
+    CommandCodec::new(&mut local)
+        .write(&test_command(1, "MSG", vec![]))
+        .expect("Command write must not fail");
+
+    let mut codec = InterceptedCodec::new(
+        CommandCodec::new(futures::io::Cursor::new(local)),
+        InterceptorChain::new().with(Tag(7)),
+    );
+
+    let command = futures::executor::block_on(codec.read_async())
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(command.data, vec![7]);
+}
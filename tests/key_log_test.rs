@@ -0,0 +1,83 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    fs,
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
+
+use loco_protocol::secure::{
+    crypto::CryptoStore,
+    session::{
+        key_log::{connection_id, format_key_log, KeyLog, KeyLogFile},
+        SecureClientSession, SecureServerSession,
+    },
+    SECURE_HANDSHAKE_HEAD_SIZE,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+#[derive(Default)]
+struct RecordingKeyLog {
+    lines: Mutex<Vec<String>>,
+}
+
+impl KeyLog for RecordingKeyLog {
+    fn log(&self, connection_id: &[u8], key: &[u8]) {
+        self.lines
+            .lock()
+            .unwrap()
+            .push(format_key_log(connection_id, key));
+    }
+}
+
+#[test]
+pub fn handshake_key_log() {
+    let private_key = RsaPrivateKey::new(&mut ChaCha20Rng::seed_from_u64(1), 1024)
+        .expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let key_log = Arc::new(RecordingKeyLog::default());
+
+    let mut local = Vec::<u8>::new();
+
+    SecureClientSession::new(public_key)
+        .with_key_log(key_log.clone())
+        .handshake(CryptoStore::new_with_key([0xab; 16]), &mut local)
+        .expect("Client handshake failed");
+
+    SecureServerSession::new(private_key)
+        .with_key_log(key_log.clone())
+        .handshake(Cursor::new(&mut local))
+        .expect("Server handshake failed");
+
+    let expected = format_key_log(
+        &connection_id(&local[SECURE_HANDSHAKE_HEAD_SIZE..]),
+        &[0xab; 16],
+    );
+
+    assert!(expected.starts_with("LOCO_AES_KEY "));
+    assert!(expected.ends_with(" abababababababababababababababab"));
+    assert_eq!(*key_log.lines.lock().unwrap(), vec![expected.clone(), expected]);
+}
+
+#[test]
+pub fn key_log_file() {
+    let path = std::env::temp_dir().join(format!("loco-keylog-{}.txt", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let key_log = KeyLogFile::new(&path).expect("Key log file open failed");
+    key_log.log(&[0x01, 0x02], &[0xff; 2]);
+    key_log.log(&[0x03], &[0x00]);
+    drop(key_log);
+
+    let content = fs::read_to_string(&path).expect("Key log file read failed");
+    fs::remove_file(&path).ok();
+
+    assert_eq!(content, "LOCO_AES_KEY 0102 ffff\nLOCO_AES_KEY 03 00\n");
+}
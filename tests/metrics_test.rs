@@ -0,0 +1,243 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use common::{test_command, Loopback};
+use loco_protocol::{
+    command::codec::CommandCodec,
+    metrics::{Metrics, Side},
+    secure::{
+        codec::SecureCodec,
+        crypto::CryptoStore,
+        session::{SecureClientSession, SecureServerSession},
+    },
+};
+use rand::rngs::OsRng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+#[derive(Debug, PartialEq)]
+enum Recorded {
+    CommandRead(String, usize),
+    CommandWritten(String, usize),
+    ResponseLatency(String),
+    PacketRead(usize),
+    PacketWritten(usize),
+    DecryptFailed,
+    Handshake,
+}
+
+#[derive(Default)]
+struct RecordingMetrics(Mutex<Vec<Recorded>>);
+
+impl RecordingMetrics {
+    fn push(&self, recorded: Recorded) {
+        self.0.lock().unwrap().push(recorded);
+    }
+
+    fn take(&self) -> Vec<Recorded> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Metrics for RecordingMetrics {
+    fn command_read(&self, method: &str, size: usize) {
+        self.push(Recorded::CommandRead(method.to_string(), size));
+    }
+
+    fn command_written(&self, method: &str, size: usize) {
+        self.push(Recorded::CommandWritten(method.to_string(), size));
+    }
+
+    fn response_latency(&self, method: &str, _: Duration) {
+        self.push(Recorded::ResponseLatency(method.to_string()));
+    }
+
+    fn packet_read(&self, size: usize) {
+        self.push(Recorded::PacketRead(size));
+    }
+
+    fn packet_written(&self, size: usize) {
+        self.push(Recorded::PacketWritten(size));
+    }
+
+    fn decrypt_failed(&self) {
+        self.push(Recorded::DecryptFailed);
+    }
+
+    fn handshake(&self, _: Duration) {
+        self.push(Recorded::Handshake);
+    }
+}
+
+#[test]
+pub fn metrics_command_response_latency() {
+    let metrics = Arc::new(RecordingMetrics::default());
+
+    let mut input = Vec::new();
+    let mut input_codec = CommandCodec::new(&mut input);
+    input_codec
+        .write(&test_command(7, "CHECKIN", vec![1; 4]))
+        .expect("Command write must not fail");
+    input_codec
+        .write(&test_command(1, "MSG", vec![2; 8]))
+        .expect("Command write must not fail");
+
+    let mut codec = CommandCodec::new(Loopback::new(input)).with_metrics(metrics.clone());
+
+    codec
+        .write(&test_command(1, "LOGINLIST", vec![0; 10]))
+        .expect("Command write must not fail");
+
+    for _ in 0..2 {
+        codec
+            .read()
+            .expect("Command read must not fail")
+            .expect("Stream must not end");
+    }
+
+    assert_eq!(
+        metrics.take(),
+        vec![
+            Recorded::CommandWritten("LOGINLIST".into(), 32),
+            Recorded::CommandRead("CHECKIN".into(), 26),
+            Recorded::CommandRead("MSG".into(), 30),
+            Recorded::ResponseLatency("LOGINLIST".into()),
+        ]
+    );
+}
+
+#[test]
+pub fn metrics_server_response_latency() {
+    let metrics = Arc::new(RecordingMetrics::default());
+
+    let mut input = Vec::new();
+    let mut input_codec = CommandCodec::new(&mut input);
+    input_codec
+        .write(&test_command(1, "LOGINLIST", vec![0; 10]))
+        .expect("Command write must not fail");
+
+    let mut codec =
+        CommandCodec::new(Loopback::new(input)).with_metrics_as(metrics.clone(), Side::Server);
+
+    codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+
+    codec
+        .write(&test_command(7, "CHECKIN", vec![1; 4]))
+        .expect("Command write must not fail");
+    codec
+        .write(&test_command(1, "LOGINLIST", vec![2; 8]))
+        .expect("Command write must not fail");
+
+    assert_eq!(
+        metrics.take(),
+        vec![
+            Recorded::CommandRead("LOGINLIST".into(), 32),
+            Recorded::CommandWritten("CHECKIN".into(), 26),
+            Recorded::CommandWritten("LOGINLIST".into(), 30),
+            Recorded::ResponseLatency("LOGINLIST".into()),
+        ]
+    );
+}
+
+#[test]
+pub fn metrics_secure_packets() {
+    let metrics = Arc::new(RecordingMetrics::default());
+    let crypto = CryptoStore::new();
+
+    let mut local = Vec::<u8>::new();
+    let mut codec = SecureCodec::new(crypto.clone(), &mut local).with_metrics(metrics.clone());
+    codec.write_data(&[1, 2, 3, 4]).expect("Data writing must not fail");
+
+    // Data size smaller than header
+    local.extend_from_slice(&4_u32.to_le_bytes());
+    local.extend_from_slice(&[0; 16]);
+
+    let mut codec = SecureCodec::new(crypto, Cursor::new(local)).with_metrics(metrics.clone());
+    codec
+        .read_packet()
+        .expect("Data reading must not fail")
+        .expect("Stream must not end");
+    assert!(codec.read_packet().is_err());
+
+    assert_eq!(
+        metrics.take(),
+        vec![
+            Recorded::PacketWritten(24),
+            Recorded::PacketRead(24),
+            Recorded::DecryptFailed,
+        ]
+    );
+}
+
+#[test]
+pub fn metrics_server_handshake() {
+    let metrics = Arc::new(RecordingMetrics::default());
+
+    let private_key = RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let mut local = Vec::<u8>::new();
+    SecureClientSession::new(public_key)
+        .handshake(CryptoStore::new(), &mut local)
+        .expect("Client handshake failed");
+
+    SecureServerSession::new(private_key)
+        .with_metrics(metrics.clone())
+        .handshake(Cursor::new(local))
+        .expect("Server handshake failed");
+
+    assert_eq!(metrics.take(), vec![Recorded::Handshake]);
+}
+
+#[test]
+pub fn metrics_unanswered_commands_evicted() {
+    let metrics = Arc::new(RecordingMetrics::default());
+
+    // Responses to first and last command
+    let mut input = Vec::new();
+    let mut input_codec = CommandCodec::new(&mut input);
+    input_codec
+        .write(&test_command(0, "MSG", vec![]))
+        .expect("Command write must not fail");
+    input_codec
+        .write(&test_command(1999, "MSG", vec![]))
+        .expect("Command write must not fail");
+
+    let mut codec = CommandCodec::new(Loopback::new(input)).with_metrics(metrics.clone());
+
+    for id in 0..2000 {
+        codec
+            .write(&test_command(id, "WRITE", vec![]))
+            .expect("Command write must not fail");
+    }
+    metrics.take();
+
+    for _ in 0..2 {
+        codec
+            .read()
+            .expect("Command read must not fail")
+            .expect("Stream must not end");
+    }
+
+    assert_eq!(
+        metrics.take(),
+        vec![
+            Recorded::CommandRead("MSG".into(), 22),
+            Recorded::CommandRead("MSG".into(), 22),
+            Recorded::ResponseLatency("WRITE".into()),
+        ]
+    );
+}
@@ -0,0 +1,287 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use loco_protocol::{
+    command::{codec::CommandCodec, Command, Header},
+    secure::stream::SecureStream,
+};
+use rand::rngs::OsRng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+/// Size of file the mock server already has for resumed upload
+const UPLOADED_OFFSET: u64 = 1000;
+
+fn test_file() -> Vec<u8> {
+    (0..70000).map(|i| (i % 253) as u8).collect()
+}
+
+fn command(method: &str, value: u64) -> Command {
+    Command {
+        header: Header {
+            id: 0,
+            status: 0,
+            method: Header::to_method(method),
+            data_type: 0,
+        },
+        data: value.to_le_bytes().to_vec(),
+    }
+}
+
+fn value(command: &Command) -> u64 {
+    u64::from_le_bytes(command.data[..8].try_into().unwrap())
+}
+
+/// Mock media server handling one DOWN or POST request.
+/// Returns data uploaded by client.
+fn serve<S: Read + Write>(stream: S, file: &[u8]) -> Vec<u8> {
+    let mut codec = CommandCodec::new(stream);
+    let (_, request) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+
+    match request.header.method().unwrap().as_str() {
+        "DOWN" => {
+            let offset = value(&request);
+            codec
+                .write(&command("DOWN", file.len() as u64))
+                .expect("Command write must not fail");
+
+            let mut raw = codec
+                .into_raw(offset, file.len() as u64)
+                .expect("Raw transfer must start");
+            raw.write_all(&file[offset as usize..])
+                .expect("Raw write must not fail");
+            raw.flush().expect("Raw flush must not fail");
+
+            let mut codec = raw.into_codec();
+            codec
+                .write(&command("COMPLETE", 0))
+                .expect("Command write must not fail");
+
+            Vec::new()
+        }
+
+        "POST" => {
+            let total = value(&request);
+            codec
+                .write(&command("POST", UPLOADED_OFFSET))
+                .expect("Command write must not fail");
+
+            let mut raw = codec
+                .into_raw(UPLOADED_OFFSET, total)
+                .expect("Raw transfer must start");
+            let mut uploaded = Vec::new();
+            raw.read_to_end(&mut uploaded)
+                .expect("Raw read must not fail");
+
+            let mut codec = raw.into_codec();
+            codec
+                .write(&command("SUCCESS", uploaded.len() as u64))
+                .expect("Command write must not fail");
+
+            uploaded
+        }
+
+        method => panic!("unexpected method {}", method),
+    }
+}
+
+fn spawn_server(secure_key: Option<RsaPrivateKey>) -> (u16, JoinHandle<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Bind must not fail");
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("Accept must not fail");
+
+        match secure_key {
+            Some(key) => serve(
+                SecureStream::accept(stream, &key).expect("Server handshake must not fail"),
+                &test_file(),
+            ),
+            None => serve(stream, &test_file()),
+        }
+    });
+
+    (port, handle)
+}
+
+/// Download file from offset and switch back to command framing
+fn download<S: Read + Write>(stream: S, offset: u64) -> (Vec<u8>, Vec<(u64, u64)>) {
+    let mut codec = CommandCodec::new(stream);
+    codec
+        .write(&command("DOWN", offset))
+        .expect("Command write must not fail");
+    let (_, response) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let mut raw = codec
+        .into_raw(offset, value(&response))
+        .expect("Raw transfer must start")
+        .with_progress({
+            let progress = progress.clone();
+            move |offset, total| progress.lock().unwrap().push((offset, total))
+        });
+
+    let mut data = Vec::new();
+    raw.read_to_end(&mut data).expect("Raw read must not fail");
+    assert!(raw.is_complete());
+
+    let mut codec = raw.into_codec();
+    let (_, complete) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(complete.header.method().unwrap(), "COMPLETE");
+
+    let progress = progress.lock().unwrap().clone();
+    (data, progress)
+}
+
+#[test]
+pub fn raw_download_resume() {
+    let file = test_file();
+    let (port, server) = spawn_server(None);
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("Connect must not fail");
+    let (data, progress) = download(stream, 5000);
+
+    assert_eq!(&data[..], &file[5000..]);
+    assert_eq!(progress.last(), Some(&(file.len() as u64, file.len() as u64)));
+    assert!(progress.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    server.join().expect("Server must not panic");
+}
+
+#[test]
+pub fn raw_download_secure() {
+    let private_key = RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let file = test_file();
+    let (port, server) = spawn_server(Some(private_key));
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("Connect must not fail");
+    let stream =
+        SecureStream::connect(stream, &public_key).expect("Client handshake must not fail");
+    let (data, _) = download(stream, 0);
+
+    assert_eq!(data, file);
+
+    server.join().expect("Server must not panic");
+}
+
+#[test]
+pub fn raw_upload_resume() {
+    let file = test_file();
+    let (port, server) = spawn_server(None);
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("Connect must not fail");
+    let mut codec = CommandCodec::new(stream);
+    codec
+        .write(&command("POST", file.len() as u64))
+        .expect("Command write must not fail");
+    let (_, response) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+
+    // Server already has part of file
+    let offset = value(&response);
+    let mut raw = codec
+        .into_raw(offset, file.len() as u64)
+        .expect("Raw transfer must start");
+    assert_eq!(raw.remaining(), file.len() as u64 - UPLOADED_OFFSET);
+
+    let written =
+        io::copy(&mut &file[offset as usize..], &mut raw).expect("Raw write must not fail");
+    raw.flush().expect("Raw flush must not fail");
+    assert_eq!(written, raw.total() - UPLOADED_OFFSET);
+
+    // Transfer is complete, more bytes are not accepted
+    assert_eq!(raw.write(&[0]).expect("Raw write must not fail"), 0);
+
+    let mut codec = raw.into_codec();
+    let (_, success) = codec
+        .read()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(success.header.method().unwrap(), "SUCCESS");
+    assert_eq!(value(&success), written);
+
+    let uploaded = server.join().expect("Server must not panic");
+    assert_eq!(&uploaded[..], &file[UPLOADED_OFFSET as usize..]);
+}
+
+#[test]
+pub fn raw_download_async() {
+    let file = test_file();
+    let offset = 100;
+
+    // Raw bytes followed by command after transfer
+    let mut local = file[offset..].to_vec();
+    CommandCodec::new(&mut local)
+        .write(&command("COMPLETE", 0))
+        .expect("Command write must not fail");
+
+    futures::executor::block_on(async {
+        let mut raw = CommandCodec::new(futures::io::Cursor::new(local))
+            .into_raw(offset as u64, file.len() as u64)
+            .expect("Raw transfer must start");
+
+        let mut data = Vec::new();
+        futures::io::copy(&mut raw, &mut data)
+            .await
+            .expect("Raw read must not fail");
+        assert_eq!(&data[..], &file[offset..]);
+
+        let (_, complete) = raw
+            .into_codec()
+            .read_async()
+            .await
+            .expect("Command read must not fail")
+            .expect("Stream must not end");
+        assert_eq!(complete.header.method().unwrap(), "COMPLETE");
+    });
+}
+
+#[test]
+pub fn raw_transfer_after_unread_body() {
+    let mut local = Vec::new();
+    CommandCodec::new(&mut local)
+        .write(&command("DOWN", 4))
+        .expect("Command write must not fail");
+    local.extend_from_slice(&[1, 2, 3, 4]);
+
+    let mut codec = CommandCodec::new(io::Cursor::new(local));
+    codec
+        .read_head()
+        .expect("Command read must not fail")
+        .expect("Stream must not end");
+
+    // Body of DOWN is not read yet
+    let mut codec = codec
+        .into_raw(0, 4)
+        .expect_err("Raw transfer must not start in middle of command")
+        .0;
+
+    codec.skip_body().expect("Body skip must not fail");
+    let mut raw = codec.into_raw(0, 4).expect("Raw transfer must start");
+
+    let mut data = Vec::new();
+    raw.read_to_end(&mut data).expect("Raw read must not fail");
+    assert_eq!(data, vec![1, 2, 3, 4]);
+}
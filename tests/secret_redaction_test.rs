@@ -0,0 +1,47 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use loco_protocol::secure::{
+    crypto::{CryptoStore, Unredacted},
+    session::SecureServerSession,
+    stream::SecureStream,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rsa::RsaPrivateKey;
+
+#[test]
+pub fn crypto_store_debug_redacted() {
+    let stream = SecureStream::from_established(CryptoStore::new_with_key([0xab; 16]), Vec::<u8>::new());
+
+    let debug = format!("{:?}", stream);
+
+    assert!(debug.contains("<redacted>"));
+    assert!(!debug.contains("171"));
+}
+
+#[test]
+pub fn server_session_debug_redacted() {
+    let private_key = RsaPrivateKey::new(&mut ChaCha20Rng::seed_from_u64(1), 512)
+        .expect("failed to generate a key");
+
+    let debug = format!("{:?}", SecureServerSession::new(private_key));
+
+    assert_eq!(
+        debug,
+        "SecureServerSession { key: <redacted>, key_log: false }"
+    );
+}
+
+#[test]
+pub fn crypto_store_debug_unredacted() {
+    let crypto = CryptoStore::new_with_key([0xab; 16]);
+
+    let debug = format!("{:?}", Unredacted(&crypto));
+
+    assert!(!debug.contains("<redacted>"));
+    assert!(debug.contains(&format!("{:?}", [0xab_u8; 16])));
+}
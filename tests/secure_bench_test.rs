@@ -0,0 +1,97 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+//! Packet throughput benchmark.
+//! Run with `cargo test --release --test secure_bench_test -- --ignored --nocapture`.
+
+use std::{
+    hint::black_box,
+    io::{self, Cursor},
+    time::{Duration, Instant},
+};
+
+use loco_protocol::secure::{codec::SecureCodec, crypto::CryptoStore, SECURE_HEAD_SIZE};
+
+const PACKETS: usize = 10000;
+const DATA_SIZE: usize = 1024;
+
+fn report(name: &str, elapsed: Duration) {
+    let bytes = (PACKETS * DATA_SIZE) as f64;
+
+    println!(
+        "{}: {:?} per packet, {:.1} MiB/s",
+        name,
+        elapsed / PACKETS as u32,
+        bytes / elapsed.as_secs_f64() / (1024.0 * 1024.0)
+    );
+}
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up buffers and rng
+    f();
+
+    let time = Instant::now();
+    for _ in 0..PACKETS {
+        f();
+    }
+    report(name, time.elapsed());
+}
+
+#[test]
+#[ignore]
+pub fn bench_aes() {
+    let crypto = CryptoStore::new();
+    let iv = [0_u8; 16];
+    let mut data = vec![1_u8; DATA_SIZE];
+
+    bench("encrypt_aes", || {
+        black_box(crypto.encrypt_aes(black_box(&data), &iv).unwrap());
+    });
+
+    bench("encrypt_aes_in_place", || {
+        crypto
+            .encrypt_aes_in_place(black_box(&mut data), &iv)
+            .unwrap();
+    });
+
+    bench("decrypt_aes_in_place", || {
+        crypto
+            .decrypt_aes_in_place(black_box(&mut data), &iv)
+            .unwrap();
+    });
+}
+
+#[test]
+#[ignore]
+pub fn bench_codec() {
+    let crypto = CryptoStore::new();
+    let data = vec![1_u8; DATA_SIZE];
+
+    let mut write_codec = SecureCodec::new(crypto.clone(), io::sink());
+    bench("write_data", || {
+        write_codec
+            .write_data(black_box(&data))
+            .expect("Packet write must not fail");
+    });
+
+    let mut local = Vec::with_capacity((SECURE_HEAD_SIZE + DATA_SIZE) * (PACKETS + 1));
+    {
+        let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+        for _ in 0..=PACKETS {
+            codec.write_data(&data).expect("Packet write must not fail");
+        }
+    }
+
+    let mut read_codec = SecureCodec::new(crypto, Cursor::new(&local));
+    let mut buf = Vec::new();
+    bench("read_packet_into", || {
+        read_codec
+            .read_packet_into(black_box(&mut buf))
+            .expect("Packet read must not fail");
+    });
+
+    assert_eq!(buf, data);
+}
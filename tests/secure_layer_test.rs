@@ -4,8 +4,11 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
+mod common;
+
 use std::io::Cursor;
 
+use common::{ChunkedReader, TrickleWriter};
 use loco_protocol::secure::{codec::SecureCodec, crypto::CryptoStore};
 
 #[test]
@@ -28,9 +31,189 @@ pub fn secure_layer_read_write() {
     // Reset read/write position
     codec.stream_mut().set_position(0);
 
-    let packet1 = codec.read_packet().expect("Data reading must not fail");
+    let packet1 = codec
+        .read_packet()
+        .expect("Data reading must not fail")
+        .expect("Stream must not end");
     assert_eq!(packet1.data, test_data1);
 
-    let packet2 = codec.read_packet().expect("Data reading must not fail");
+    let packet2 = codec
+        .read_packet()
+        .expect("Data reading must not fail")
+        .expect("Stream must not end");
     assert_eq!(packet2.data, test_data2);
 }
+
+#[test]
+pub fn secure_layer_read_async_cancel() {
+    use futures::FutureExt;
+
+    let mut local = Vec::<u8>::new();
+
+    let crypto = CryptoStore::new();
+    let mut codec = SecureCodec::new(crypto.clone(), Cursor::new(&mut local));
+
+    let test_data = (0..3_u8).map(|i| vec![i; 40]).collect::<Vec<_>>();
+    for data in &test_data {
+        codec.write_data(data).expect("Data writing must not fail");
+    }
+
+    let mut codec = SecureCodec::new(
+        crypto,
+        ChunkedReader::new(futures::io::Cursor::new(local), 5),
+    );
+
+    for data in &test_data {
+        // Poll once and drop future, like a lost select! branch
+        let packet = loop {
+            if let Some(res) = codec.read_packet_async().now_or_never() {
+                break res
+                    .expect("Data reading must not fail")
+                    .expect("Stream must not end");
+            }
+        };
+
+        assert_eq!(&packet.data, data);
+    }
+}
+
+#[test]
+pub fn secure_layer_write_async_cancel() {
+    use futures::FutureExt;
+
+    let crypto = CryptoStore::new();
+    let mut codec = SecureCodec::new(crypto.clone(), TrickleWriter::new());
+
+    // Dropped after first poll. Next call finishes its packet first.
+    assert!(codec.write_data_async(&[1; 40]).now_or_never().is_none());
+    assert!(codec.is_write_pending());
+
+    futures::executor::block_on(codec.write_data_async(&[2; 40]))
+        .expect("Data writing must not fail");
+
+    let (_, writer) = codec.into_inner();
+    let mut codec = SecureCodec::new(crypto, Cursor::new(writer.inner));
+    for data in [[1; 40], [2; 40]] {
+        let packet = codec
+            .read_packet()
+            .expect("Data reading must not fail")
+            .expect("Stream must not end");
+
+        assert_eq!(packet.data, data);
+    }
+}
+
+#[test]
+#[should_panic]
+pub fn secure_layer_start_write_while_pending() {
+    use futures::FutureExt;
+
+    let mut codec = SecureCodec::new(CryptoStore::new(), TrickleWriter::new());
+
+    codec
+        .start_write(&[1, 2, 3, 4])
+        .expect("Data encryption must not fail");
+    assert!(futures::future::poll_fn(|cx| codec.poll_write_pending(cx))
+        .now_or_never()
+        .is_none());
+
+    let _ = codec.start_write(&[5, 6, 7, 8]);
+}
+
+/// Reader counting read calls
+struct CountingReader<R> {
+    inner: R,
+    reads: usize,
+}
+
+impl<R: std::io::Read> std::io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        self.inner.read(buf)
+    }
+}
+
+impl<R: futures::AsyncRead + Unpin> futures::AsyncRead for CountingReader<R> {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        self.reads += 1;
+        std::pin::Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+fn write_test_packets(crypto: &CryptoStore, test_data: &[Vec<u8>]) -> Vec<u8> {
+    let mut local = Vec::<u8>::new();
+
+    let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+    for data in test_data {
+        codec.write_data(data).expect("Data writing must not fail");
+    }
+
+    local
+}
+
+#[test]
+pub fn secure_layer_read_ahead() {
+    let crypto = CryptoStore::new();
+
+    let mut test_data = (0..10_u8).map(|i| vec![i; 100]).collect::<Vec<_>>();
+    test_data.push(vec![10; 5000]);
+    let local = write_test_packets(&crypto, &test_data);
+
+    let mut codec = SecureCodec::with_read_buffer_size(
+        crypto,
+        CountingReader {
+            inner: Cursor::new(local),
+            reads: 0,
+        },
+        4096,
+    );
+
+    for data in &test_data[..10] {
+        let packet = codec
+            .read_packet()
+            .expect("Data reading must not fail")
+            .expect("Stream must not end");
+        assert_eq!(&packet.data, data);
+    }
+    assert_eq!(codec.stream().reads, 1);
+
+    // Packet larger than buffer
+    let packet = codec
+        .read_packet()
+        .expect("Data reading must not fail")
+        .expect("Stream must not end");
+    assert_eq!(packet.data, test_data[10]);
+    assert!(codec.read_buffer().is_empty());
+}
+
+#[test]
+pub fn secure_layer_read_ahead_async() {
+    let crypto = CryptoStore::new();
+
+    let test_data = (0..10_u8).map(|i| vec![i; 100]).collect::<Vec<_>>();
+    let local = write_test_packets(&crypto, &test_data);
+
+    let mut codec = SecureCodec::new(
+        crypto,
+        CountingReader {
+            inner: futures::io::Cursor::new(local),
+            reads: 0,
+        },
+    );
+
+    futures::executor::block_on(async {
+        for data in &test_data {
+            let packet = codec
+                .read_packet_async()
+                .await
+                .expect("Data reading must not fail")
+                .expect("Stream must not end");
+            assert_eq!(&packet.data, data);
+        }
+    });
+    assert_eq!(codec.stream().reads, 1);
+}
@@ -4,29 +4,207 @@
  * Copyright (c) storycraft. Licensed under the MIT Licence.
  */
 
+mod common;
+
 use std::io::{Cursor, Read, Write};
 
-use loco_protocol::secure::{crypto::CryptoStore, stream::SecureStream};
+use common::{ChunkedReader, TrickleWriter};
+use loco_protocol::secure::{
+    crypto::CryptoStore,
+    stream::{SecureStream, DEFAULT_MAX_PACKET_SIZE},
+};
 
 #[test]
 pub fn secure_stream_read_write() {
     let mut local = Vec::<u8>::new();
 
     let crypto = CryptoStore::new();
-    let mut stream = SecureStream::new(crypto, Cursor::new(&mut local));
+    let mut stream = SecureStream::from_established(crypto, Cursor::new(&mut local));
 
     let test_data = vec![1_u8, 2, 3, 4];
 
     stream
-        .write(&test_data)
+        .write_all(&test_data)
         .expect("Data writing must not fail");
+    stream.flush().expect("Data flushing must not fail");
 
     // Reset read/write position
     stream.stream_mut().set_position(0);
 
     let mut data = vec![0_u8; 4];
 
-    stream.read(&mut data).expect("Data reading must not fail");
+    stream.read_exact(&mut data).expect("Data reading must not fail");
 
     assert_eq!(test_data, data);
 }
+
+#[test]
+pub fn secure_stream_read_trickle() {
+    use futures::AsyncReadExt;
+
+    let mut local = Vec::<u8>::new();
+
+    let crypto = CryptoStore::new();
+    let mut writer = SecureStream::from_established(crypto.clone(), &mut local);
+
+    let test_data = (0..3000).map(|i| i as u8).collect::<Vec<u8>>();
+    for chunk in test_data.chunks(1000) {
+        writer.write_all(chunk).expect("Data writing must not fail");
+    }
+    writer.flush().expect("Data flushing must not fail");
+
+    let mut stream = SecureStream::from_established(
+        crypto,
+        ChunkedReader::new(futures::io::Cursor::new(local), 1),
+    );
+
+    let mut data = vec![0_u8; test_data.len()];
+    futures::executor::block_on(stream.read_exact(&mut data)).expect("Data reading must not fail");
+
+    assert_eq!(test_data, data);
+}
+
+#[test]
+pub fn secure_stream_write_trickle() {
+    use futures::AsyncWriteExt;
+    use loco_protocol::secure::codec::SecureCodec;
+
+    let crypto = CryptoStore::new();
+    let mut stream = SecureStream::from_established(crypto.clone(), TrickleWriter::new());
+
+    let test_data = (0..300).map(|i| i as u8).collect::<Vec<u8>>();
+    futures::executor::block_on(async {
+        for chunk in test_data.chunks(100) {
+            stream.write_all(chunk).await.expect("Data writing must not fail");
+            stream.flush().await.expect("Data flushing must not fail");
+        }
+    });
+
+    let (_, writer) = stream.into_inner();
+    assert_eq!(writer.inner.len(), 3 * (100 + 20));
+
+    let mut codec = SecureCodec::new(crypto, Cursor::new(writer.inner));
+    for chunk in test_data.chunks(100) {
+        let packet = codec
+            .read_packet()
+            .expect("Data reading must not fail")
+            .expect("Stream must not end");
+        assert_eq!(packet.data, chunk);
+    }
+}
+
+#[test]
+pub fn secure_stream_write_during_pending_flush() {
+    use futures::{AsyncWriteExt, FutureExt};
+    use loco_protocol::secure::codec::SecureCodec;
+
+    let crypto = CryptoStore::new();
+    let mut stream = SecureStream::from_established(crypto.clone(), TrickleWriter::new());
+
+    futures::executor::block_on(async {
+        stream.write_all(b"hello").await.expect("Data writing must not fail");
+
+        // Dropped while packet is partially written
+        assert!(stream.flush().now_or_never().is_none());
+
+        stream.write_all(b" world").await.expect("Data writing must not fail");
+        stream.flush().await.expect("Data flushing must not fail");
+    });
+
+    let (_, writer) = stream.into_inner();
+    let mut codec = SecureCodec::new(crypto, Cursor::new(writer.inner));
+
+    let mut data = Vec::new();
+    while let Some(packet) = codec.read_packet().expect("Data reading must not fail") {
+        data.extend_from_slice(&packet.data);
+    }
+    assert_eq!(data, b"hello world");
+}
+
+#[test]
+pub fn secure_stream_max_packet_size() {
+    use loco_protocol::secure::codec::SecureCodec;
+
+    let mut local = Vec::<u8>::new();
+
+    let crypto = CryptoStore::new();
+    let mut stream = SecureStream::from_established(crypto.clone(), &mut local);
+    assert_eq!(stream.max_packet_size(), Some(DEFAULT_MAX_PACKET_SIZE));
+    stream.set_max_packet_size(Some(4));
+
+    stream.write_all(&[1, 2, 3]).expect("Data writing must not fail");
+    stream.write_all(&[4, 5, 6, 7, 8, 9]).expect("Data writing must not fail");
+    stream.flush().expect("Data flushing must not fail");
+
+    let mut codec = SecureCodec::new(crypto, Cursor::new(local));
+
+    for chunk in [&[1_u8, 2, 3, 4][..], &[5, 6, 7, 8], &[9]] {
+        let packet = codec
+            .read_packet()
+            .expect("Data reading must not fail")
+            .expect("Stream must not end");
+        assert_eq!(packet.data, chunk);
+    }
+
+    assert_eq!(codec.stream().position() as usize, codec.stream().get_ref().len());
+}
+
+#[test]
+pub fn secure_stream_buf_read() {
+    use loco_protocol::secure::codec::SecureCodec;
+    use std::io::BufRead;
+
+    let mut local = Vec::<u8>::new();
+
+    let crypto = CryptoStore::new();
+    let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+
+    let test_data = (0..10000).map(|i| i as u8).collect::<Vec<u8>>();
+    codec.write_data(&[]).expect("Data writing must not fail");
+    codec.write_data(&test_data).expect("Data writing must not fail");
+    codec.write_data(b"line\n").expect("Data writing must not fail");
+
+    let mut stream = SecureStream::from_established(crypto, Cursor::new(local));
+
+    // Empty packet is skipped and whole packet is exposed without copy
+    assert_eq!(stream.fill_buf().unwrap(), &test_data[..]);
+    stream.consume(3);
+
+    let mut data = test_data[..3].to_vec();
+    let mut buf = [0_u8; 7];
+    while data.len() < test_data.len() {
+        let read = stream.read(&mut buf).expect("Data reading must not fail");
+        data.extend_from_slice(&buf[..read]);
+    }
+    assert_eq!(data, test_data);
+
+    let mut line = String::new();
+    stream.read_line(&mut line).expect("Line reading must not fail");
+    assert_eq!(line, "line\n");
+}
+
+#[test]
+pub fn secure_stream_async_buf_read() {
+    use futures::AsyncBufReadExt;
+    use loco_protocol::secure::codec::SecureCodec;
+
+    let mut local = Vec::<u8>::new();
+
+    let crypto = CryptoStore::new();
+    let mut codec = SecureCodec::new(crypto.clone(), &mut local);
+
+    codec.write_data(b"first ").expect("Data writing must not fail");
+    codec.write_data(b"line\nsecond line\n").expect("Data writing must not fail");
+
+    let mut stream = SecureStream::from_established(crypto, futures::io::Cursor::new(local));
+
+    futures::executor::block_on(async {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.expect("Line reading must not fail");
+        assert_eq!(line, "first line\n");
+
+        line.clear();
+        stream.read_line(&mut line).await.expect("Line reading must not fail");
+        assert_eq!(line, "second line\n");
+    });
+}
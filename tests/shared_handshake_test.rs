@@ -0,0 +1,152 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use futures::{executor::LocalPool, io::Cursor, task::LocalSpawnExt};
+use loco_protocol::secure::{
+    crypto::CryptoStore,
+    session::{
+        client::to_handshake_packet,
+        shared::{BlockingExecutor, SharedServerSession},
+    },
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Executor queueing tasks until test runs them
+#[derive(Default)]
+struct ManualExecutor {
+    tasks: Mutex<Vec<Task>>,
+}
+
+impl ManualExecutor {
+    fn take(&self) -> Vec<Task> {
+        std::mem::take(&mut *self.tasks.lock().unwrap())
+    }
+}
+
+impl BlockingExecutor for ManualExecutor {
+    fn spawn_blocking(&self, task: Task) {
+        self.tasks.lock().unwrap().push(task);
+    }
+}
+
+#[test]
+pub fn shared_handshake() {
+    let private_key = RsaPrivateKey::new(&mut ChaCha20Rng::seed_from_u64(1), 1024)
+        .expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let executor = Arc::new(ManualExecutor::default());
+    let session = SharedServerSession::new(private_key, executor.clone(), 2);
+
+    let mut pool = LocalPool::new();
+    let results = Rc::new(RefCell::new(Vec::new()));
+
+    for i in 0..6_u8 {
+        let session = session.clone();
+        let results = results.clone();
+        let packet = to_handshake_packet(&CryptoStore::new_with_key([i; 16]), &public_key)
+            .expect("Handshake packet failed");
+
+        pool.spawner()
+            .spawn_local(async move {
+                let stream = session
+                    .handshake_async(Cursor::new(packet))
+                    .await
+                    .expect("Server handshake failed");
+
+                results.borrow_mut().push((i, stream));
+            })
+            .unwrap();
+    }
+
+    for _ in 0..3 {
+        pool.run_until_stalled();
+
+        let tasks = executor.take();
+        assert_eq!(tasks.len(), 2);
+
+        for task in tasks {
+            task();
+        }
+    }
+
+    pool.run_until_stalled();
+    assert!(executor.take().is_empty());
+
+    let iv = [0_u8; 16];
+    let results = results.borrow();
+    assert_eq!(results.len(), 6);
+
+    for (i, stream) in results.iter() {
+        let encrypted = CryptoStore::new_with_key([*i; 16])
+            .encrypt_aes(&[1, 2, 3], &iv)
+            .unwrap();
+
+        assert_eq!(
+            stream.crypto().decrypt_aes(&encrypted, &iv).unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+}
+
+#[test]
+pub fn shared_handshake_cancel_keeps_permit() {
+    use futures::FutureExt;
+
+    let private_key = RsaPrivateKey::new(&mut ChaCha20Rng::seed_from_u64(1), 1024)
+        .expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let executor = Arc::new(ManualExecutor::default());
+    let session = SharedServerSession::new(private_key, executor.clone(), 1);
+
+    let packet = |i: u8| {
+        to_handshake_packet(&CryptoStore::new_with_key([i; 16]), &public_key)
+            .expect("Handshake packet failed")
+    };
+
+    // Poll once until decrypt is spawned, then drop it like a timed out handshake
+    let mut canceled_stream = Cursor::new(packet(0));
+    assert!(session
+        .handshake_async(&mut canceled_stream)
+        .now_or_never()
+        .is_none());
+    let canceled_tasks = executor.take();
+    assert_eq!(canceled_tasks.len(), 1);
+
+    let mut stream = Cursor::new(packet(1));
+    let mut handshake = Box::pin(session.handshake_async(&mut stream));
+    assert!((&mut handshake).now_or_never().is_none());
+
+    // Decrypt of canceled handshake still holds the only permit
+    assert!(executor.take().is_empty());
+
+    for task in canceled_tasks {
+        task();
+    }
+
+    assert!((&mut handshake).now_or_never().is_none());
+    let tasks = executor.take();
+    assert_eq!(tasks.len(), 1);
+    for task in tasks {
+        task();
+    }
+
+    (&mut handshake)
+        .now_or_never()
+        .expect("Handshake must be complete")
+        .expect("Server handshake failed");
+}
@@ -0,0 +1,168 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+mod common;
+
+use std::{
+    collections::VecDeque,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use common::test_commands;
+use futures::{executor::block_on, join, AsyncRead, AsyncWrite};
+use loco_protocol::{
+    command::codec::CommandCodec,
+    secure::{crypto::CryptoStore, stream::SecureStream},
+};
+
+#[derive(Debug, Default)]
+struct LoopbackState {
+    buf: VecDeque<u8>,
+    waker: Option<Waker>,
+}
+
+/// Stream which reads back what is written. Read waits until data is written.
+#[derive(Debug, Default, Clone)]
+struct Loopback(Arc<Mutex<LoopbackState>>);
+
+impl AsyncRead for Loopback {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut state = self.0.lock().unwrap();
+
+        if state.buf.is_empty() {
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let len = buf.len().min(state.buf.len());
+        for (dst, src) in buf.iter_mut().zip(state.buf.drain(..len)) {
+            *dst = src;
+        }
+
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl AsyncWrite for Loopback {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut state = self.0.lock().unwrap();
+
+        state.buf.extend(buf);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+pub fn secure_stream_split() {
+    let test_commands = test_commands();
+
+    let stream = SecureStream::from_established(CryptoStore::new(), Loopback::default());
+    let (read, write) = stream.split();
+
+    let mut read_codec = CommandCodec::new(read);
+    let mut write_codec = CommandCodec::new(write);
+
+    block_on(async {
+        let reader = async {
+            let mut commands = Vec::new();
+            for _ in 0..test_commands.len() {
+                let (_, command) = read_codec
+                    .read_async()
+                    .await
+                    .expect("Command read must not fail")
+                    .expect("Stream must not end");
+                commands.push(command);
+            }
+
+            commands
+        };
+
+        let writer = async {
+            for command in &test_commands {
+                write_codec
+                    .write_async(command)
+                    .await
+                    .expect("Command write must not fail");
+            }
+        };
+
+        let (commands, _) = join!(reader, writer);
+        assert_eq!(commands, test_commands);
+    });
+
+    read_codec
+        .into_inner()
+        .reunite(write_codec.into_inner())
+        .expect("Reunite must not fail");
+}
+
+#[test]
+pub fn command_codec_split() {
+    let test_commands = test_commands();
+
+    let (mut read, mut write) = CommandCodec::new(Loopback::default()).split();
+
+    block_on(async {
+        let reader = async {
+            let mut commands = Vec::new();
+            for _ in 0..test_commands.len() {
+                let (_, command) = read
+                    .read_async()
+                    .await
+                    .expect("Command read must not fail")
+                    .expect("Stream must not end");
+                commands.push(command);
+            }
+
+            commands
+        };
+
+        let writer = async {
+            for command in &test_commands {
+                write
+                    .write_async(command)
+                    .await
+                    .expect("Command write must not fail");
+            }
+        };
+
+        let (commands, _) = join!(reader, writer);
+        assert_eq!(commands, test_commands);
+    });
+
+    let (other_read, other_write) = CommandCodec::new(Loopback::default()).split();
+
+    let err = read
+        .reunite(other_write)
+        .expect_err("Reunite with other half must fail");
+    let (read, _) = (err.0, err.1);
+
+    read.reunite(write).expect("Reunite must not fail");
+    drop(other_read);
+}
@@ -0,0 +1,153 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::io::{self, Cursor, Read};
+
+use futures::executor::block_on;
+use loco_protocol::{
+    command::{
+        codec::{CommandCodec, StreamError},
+        Header, HEAD_SIZE,
+    },
+    secure::{codec::SecureCodec, crypto::CryptoStore, stream::SecureStream},
+};
+
+const BODY_SIZE: usize = 50000;
+
+fn test_header() -> Header {
+    Header {
+        id: 1,
+        status: 0,
+        method: Header::to_method("POST"),
+        data_type: 0,
+    }
+}
+
+fn test_body() -> Vec<u8> {
+    (0..BODY_SIZE).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+pub fn streaming_write_read() {
+    let crypto = CryptoStore::new();
+    let body = test_body();
+
+    let mut local = Vec::new();
+    let mut codec = CommandCodec::new(SecureStream::from_established(crypto.clone(), &mut local));
+    let written = codec
+        .write_streaming(&test_header(), BODY_SIZE, &mut Cursor::new(&body))
+        .expect("Streaming write must not fail");
+    assert_eq!(written, HEAD_SIZE + BODY_SIZE);
+    drop(codec);
+
+    // Body is split across several packets
+    let mut packets = 0;
+    let mut frame = Vec::new();
+    let mut secure_codec = SecureCodec::new(crypto.clone(), Cursor::new(&local));
+    while let Some(packet) = secure_codec
+        .read_packet()
+        .expect("Packet read must not fail")
+    {
+        packets += 1;
+        frame.extend_from_slice(&packet.data);
+    }
+    assert!(packets > 1);
+    assert_eq!(&frame[HEAD_SIZE..], &body[..]);
+
+    let mut codec = CommandCodec::new(SecureStream::from_established(crypto, Cursor::new(&local)));
+    let (header, data_size) = codec
+        .read_head()
+        .expect("Head read must not fail")
+        .expect("Stream must not end");
+    assert_eq!(header, test_header());
+    assert_eq!(data_size, BODY_SIZE);
+
+    let mut reader = codec.body_reader();
+    assert_eq!(reader.remaining(), BODY_SIZE);
+
+    let mut read = Vec::new();
+    reader
+        .read_to_end(&mut read)
+        .expect("Body read must not fail");
+    assert_eq!(read, body);
+
+    assert!(codec.read_head().expect("Head read must not fail").is_none());
+}
+
+#[test]
+pub fn streaming_write_read_async() {
+    let crypto = CryptoStore::new();
+    let body = test_body();
+
+    block_on(async {
+        let mut codec = CommandCodec::new(SecureStream::from_established(
+            crypto.clone(),
+            futures::io::Cursor::new(Vec::new()),
+        ));
+        codec
+            .write_streaming_async(
+                &test_header(),
+                BODY_SIZE,
+                &mut futures::io::Cursor::new(&body),
+            )
+            .await
+            .expect("Streaming write must not fail");
+        let (_, local) = codec.into_inner().into_inner();
+
+        let mut codec = CommandCodec::new(SecureStream::from_established(
+            crypto,
+            futures::io::Cursor::new(local.into_inner()),
+        ));
+        let (_, data_size) = codec
+            .read_head_async()
+            .await
+            .expect("Head read must not fail")
+            .expect("Stream must not end");
+        assert_eq!(data_size, BODY_SIZE);
+
+        let mut read = Vec::new();
+        futures::io::copy(codec.body_reader(), &mut read)
+            .await
+            .expect("Body read must not fail");
+        assert_eq!(read, body);
+    });
+}
+
+#[test]
+pub fn streaming_write_short_reader() {
+    let mut local = Vec::new();
+    let mut codec = CommandCodec::new(&mut local);
+
+    let res = codec.write_streaming(&test_header(), 100, &mut Cursor::new(&[0_u8; 10]));
+    assert!(matches!(
+        res,
+        Err(StreamError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+    ));
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+pub fn streaming_too_large_body() {
+    let len = u32::MAX as usize + 1;
+
+    let mut local = Vec::new();
+    let mut codec = CommandCodec::new(&mut local);
+    let err = codec
+        .write_streaming(&test_header(), len, &mut io::empty())
+        .expect_err("Oversized body must fail");
+    assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidInput);
+
+    let err = block_on(codec.write_streaming_async(
+        &test_header(),
+        len,
+        &mut futures::io::empty(),
+    ))
+    .expect_err("Oversized body must fail");
+    assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidInput);
+
+    drop(codec);
+    assert!(local.is_empty());
+}
@@ -0,0 +1,265 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use std::{
+    fmt::Debug,
+    io::{Cursor, Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use loco_protocol::{
+    command::{codec::CommandCodec, Command, Header},
+    secure::{
+        crypto::CryptoStore,
+        session::{SecureClientSession, SecureServerSession},
+        stream::SecureStream,
+    },
+};
+use rand::rngs::OsRng;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+/// Recorded span or event with its fields formatted
+#[derive(Debug)]
+struct Recorded {
+    /// Id of span, [None] if event
+    id: Option<Id>,
+    name: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Recorded {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+/// Subscriber recording every span and event
+#[derive(Default, Clone)]
+struct RecordingSubscriber {
+    records: Arc<Mutex<Vec<Recorded>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl RecordingSubscriber {
+    fn take(&self) -> Vec<Recorded> {
+        std::mem::take(&mut *self.records.lock().unwrap())
+    }
+}
+
+impl Subscriber for RecordingSubscriber {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Vec::new();
+        span.record(&mut FieldVisitor(&mut fields));
+
+        let id = Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        self.records.lock().unwrap().push(Recorded {
+            id: Some(id.clone()),
+            name: span.metadata().name().to_string(),
+            fields,
+        });
+
+        id
+    }
+
+    fn record(&self, id: &Id, values: &Record<'_>) {
+        let mut records = self.records.lock().unwrap();
+
+        if let Some(span) = records
+            .iter_mut()
+            .find(|record| record.id.as_ref() == Some(id))
+        {
+            values.record(&mut FieldVisitor(&mut span.fields));
+        }
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Vec::new();
+        event.record(&mut FieldVisitor(&mut fields));
+
+        let name = fields
+            .iter()
+            .find(|(field, _)| field == "message")
+            .map(|(_, message)| message.clone())
+            .unwrap_or_default();
+
+        self.records.lock().unwrap().push(Recorded {
+            id: None,
+            name,
+            fields,
+        });
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn find<'a>(records: &'a [Recorded], name: &str) -> &'a Recorded {
+    records
+        .iter()
+        .find(|record| record.name == name)
+        .unwrap_or_else(|| panic!("{} must be recorded", name))
+}
+
+#[test]
+pub fn tracing_command_codec() {
+    let subscriber = RecordingSubscriber::default();
+
+    let command = Command {
+        header: Header {
+            id: 3,
+            data_type: 1,
+            status: 0,
+            method: Header::to_method("LOGINLIST"),
+        },
+        data: vec![0x5a_u8; 8],
+    };
+
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        let mut local = Vec::<u8>::new();
+        CommandCodec::new(&mut local)
+            .write(&command)
+            .expect("Command write must not fail");
+
+        CommandCodec::new(Cursor::new(local))
+            .read()
+            .expect("Command read must not fail")
+            .expect("Stream must not end");
+    });
+
+    let records = subscriber.take();
+
+    let span = find(&records, "command_write");
+    assert_eq!(span.field("id"), Some("3"));
+    assert_eq!(span.field("method"), Some("LOGINLIST"));
+    assert_eq!(span.field("data_type"), Some("1"));
+    assert_eq!(span.field("size"), Some("8"));
+
+    let span = find(&records, "command_read");
+    assert_eq!(span.field("id"), Some("3"));
+    assert_eq!(span.field("method"), Some("LOGINLIST"));
+    assert_eq!(span.field("size"), Some("8"));
+
+    let read = find(&records, "command read");
+    assert_eq!(read.field("method"), Some("LOGINLIST"));
+    assert_eq!(read.field("status"), Some("0"));
+
+    assert!(records.iter().all(|record| record.field("data").is_none()));
+}
+
+#[test]
+pub fn tracing_command_body_opt_in() {
+    let subscriber = RecordingSubscriber::default();
+
+    let command = Command {
+        header: Header {
+            id: 4,
+            data_type: 0,
+            status: 0,
+            method: Header::to_method("WRITE"),
+        },
+        data: vec![0x5a_u8; 4],
+    };
+
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        let mut local = Vec::<u8>::new();
+        CommandCodec::new(&mut local)
+            .with_body_tracing()
+            .write(&command)
+            .expect("Command write must not fail");
+    });
+
+    let records = subscriber.take();
+
+    assert_eq!(
+        find(&records, "command body").field("data"),
+        Some("[90, 90, 90, 90]")
+    );
+}
+
+#[test]
+pub fn tracing_secure_handshake() {
+    let subscriber = RecordingSubscriber::default();
+
+    let private_key = RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key");
+    let public_key = RsaPublicKey::from(&private_key);
+
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        let mut local = Vec::<u8>::new();
+
+        let mut client = SecureClientSession::new(public_key)
+            .handshake(CryptoStore::new(), &mut local)
+            .expect("Client handshake failed");
+        client.write_all(&[1, 2, 3, 4]).expect("Data writing must not fail");
+        client.flush().expect("Data flushing must not fail");
+
+        let mut data = [0_u8; 4];
+        SecureServerSession::new(private_key)
+            .handshake(Cursor::new(local))
+            .expect("Server handshake failed")
+            .read_exact(&mut data)
+            .expect("Data reading must not fail");
+    });
+
+    let records = subscriber.take();
+
+    let handshakes = records
+        .iter()
+        .filter(|record| record.name == "secure handshake")
+        .collect::<Vec<_>>();
+    assert_eq!(handshakes.len(), 2);
+    for handshake in handshakes {
+        assert_eq!(handshake.field("key_encrypt_type"), Some("12"));
+        assert_eq!(handshake.field("encrypt_type"), Some("2"));
+        assert_eq!(handshake.field("key_size"), Some("128"));
+    }
+
+    assert_eq!(find(&records, "secure packet written").field("size"), Some("24"));
+    assert_eq!(find(&records, "secure packet read").field("data_size"), Some("4"));
+
+    assert!(records.iter().all(|record| record.field("aes_key").is_none()));
+}
+
+#[test]
+pub fn tracing_decrypt_failure() {
+    let subscriber = RecordingSubscriber::default();
+
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        let mut packet = 4_u32.to_le_bytes().to_vec();
+        packet.extend_from_slice(&[0; 16]);
+
+        let mut stream = SecureStream::from_established(CryptoStore::new(), Cursor::new(packet));
+        assert!(stream.read(&mut [0; 4]).is_err());
+    });
+
+    let records = subscriber.take();
+    find(&records, "secure packet decryption failed");
+}
@@ -0,0 +1,80 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * Copyright (c) storycraft. Licensed under the MIT Licence.
+ */
+
+use loco_protocol::{
+    command::{
+        codec::{
+            decode::decode_header,
+            encode::{encode_frame, encode_head},
+        },
+        Command, Header,
+    },
+    secure::{SecureHandshakeHeader, SecureHeader},
+};
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn test_header() -> Header {
+    Header {
+        id: 0x01020304,
+        status: -2,
+        method: Header::to_method("LOGINLIST"),
+        data_type: -1,
+    }
+}
+
+// Golden bytes were captured from previous bincode based implementation
+
+#[test]
+pub fn header_golden() {
+    let header = test_header();
+    let bytes = header.to_bytes();
+
+    assert_eq!(hex(&bytes), "04030201feff4c4f47494e4c4953540000ff");
+    assert_eq!(Header::from_bytes(&bytes), header);
+}
+
+#[test]
+pub fn command_head_golden() {
+    let command = Command {
+        header: test_header(),
+        data: vec![0xaa; 3],
+    };
+
+    let head = encode_head(&command);
+    assert_eq!(hex(&head), "04030201feff4c4f47494e4c4953540000ff03000000");
+    assert_eq!(hex(&encode_frame(&command)), format!("{}aaaaaa", hex(&head)));
+
+    assert_eq!(decode_header(&head), (command.header, 3));
+}
+
+#[test]
+pub fn secure_header_golden() {
+    let header = SecureHeader {
+        iv: core::array::from_fn(|i| i as u8),
+    };
+    let bytes = header.to_bytes();
+
+    assert_eq!(hex(&bytes), "000102030405060708090a0b0c0d0e0f");
+    assert_eq!(SecureHeader::from_bytes(&bytes), header);
+}
+
+#[test]
+pub fn secure_handshake_header_golden() {
+    let header = SecureHandshakeHeader {
+        key_encrypt_type: 12,
+        encrypt_type: 2,
+    };
+    let bytes = header.to_bytes();
+
+    assert_eq!(hex(&bytes), "0c00000002000000");
+
+    let decoded = SecureHandshakeHeader::from_bytes(&bytes);
+    assert_eq!(decoded.key_encrypt_type, 12);
+    assert_eq!(decoded.encrypt_type, 2);
+}